@@ -0,0 +1,99 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    token::Comma,
+    DeriveInput, Expr, Ident, LitStr, Result, Token,
+};
+
+// 属性中的一项：name = expr
+struct AttrItem {
+    name: Ident,
+    value: Expr,
+}
+
+impl Parse for AttrItem {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse::<Ident>()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse::<Expr>()?;
+        Ok(AttrItem { name, value })
+    }
+}
+
+/// 为资产类型实现TAssetCapacity
+///
+/// ```ignore
+/// #[derive(AssetCapacity)]
+/// #[asset_capacity(name = "MY_ASSET", min = 1024 * 1024, max = 10 * 1024 * 1024, timeout = 10 * 1000)]
+/// struct MyAsset;
+/// ```
+/// 所有属性都可省略：name默认为类型名的大写蛇形（MyAsset -> MY_ASSET），
+/// min、max、timeout、ref_garbage默认取AssetCapacity::default()
+#[proc_macro_derive(AssetCapacity, attributes(asset_capacity))]
+pub fn derive_asset_capacity(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    match asset_capacity(ast) {
+        Ok(r) => r,
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn asset_capacity(ast: DeriveInput) -> Result<TokenStream> {
+    let mut name: Expr = {
+        let s = LitStr::new(&upper_snake(&ast.ident.to_string()), Span::call_site());
+        syn::parse_quote!(#s)
+    };
+    let mut fields = Vec::new();
+    for attr in ast.attrs.iter().filter(|r| r.path.is_ident("asset_capacity")) {
+        let items = attr.parse_args_with(Punctuated::<AttrItem, Comma>::parse_terminated)?;
+        for item in items {
+            let value = item.value;
+            match item.name.to_string().as_str() {
+                "name" => name = value,
+                "min" | "max" | "timeout" => {
+                    let field = item.name;
+                    fields.push(quote! { r.#field = #value; });
+                }
+                "ref_garbage" => fields.push(quote! { r.flag = #value; }),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        item.name,
+                        "unknown asset_capacity attribute, expected one of: name, min, max, timeout, ref_garbage",
+                    ))
+                }
+            }
+        }
+    }
+
+    let ident = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(TokenStream::from(quote! {
+        impl #impl_generics pi_bevy_asset::TAssetCapacity for #ident #ty_generics #where_clause {
+            const ASSET_TYPE: &'static str = #name;
+            #[allow(unused_mut)]
+            fn capacity() -> pi_bevy_asset::AssetCapacity {
+                let mut r = pi_bevy_asset::AssetCapacity::default();
+                #(#fields)*
+                r
+            }
+        }
+    }))
+}
+
+// MyAsset -> MY_ASSET
+fn upper_snake(s: &str) -> String {
+    let mut r = String::with_capacity(s.len() + 4);
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            r.push('_');
+        }
+        r.extend(c.to_uppercase());
+    }
+    r
+}
@@ -0,0 +1,153 @@
+//! 资产统计
+//! 每次整理资产后，更新每个资产管理器的统计信息（AssetAccount），并检查占用阈值，越过阈值时发出AssetThresholdEvent
+//!
+//! 统计的局限：
+//! * 命中、未命中次数只统计通过ShareAssetMgr::get取资产的情况，直接通过AssetMgr（如ShareAssetMgr.0、Deref）访问的不会被统计
+//! * 整理掉的容量（evicted）由每次整理前后已用容量的差值推算，整理期间新放入的资产会使其偏小，不是精确的释放量
+
+use bevy_ecs::{
+	event::{Event, EventWriter},
+	system::{Res, ResMut, Resource},
+};
+use serde::Serialize;
+
+use crate::{AssetCollectPolicy, Allocator};
+
+/// 资产管理器的统计信息
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AssetMgrStats {
+	/// 资产类型名
+	pub name: Option<String>,
+	/// 已用容量
+	pub size: usize,
+	/// 资产数量（包括未被引用、等待整理的资产），只有通过ShareAssetMgr创建的资产管理器可以统计，其他为None
+	pub count: Option<usize>,
+	/// 容量分配器分配的容量
+	pub capacity: usize,
+	/// 注册的最小、最大容量
+	pub min: usize,
+	pub max: usize,
+	/// 命中、未命中次数（只统计通过ShareAssetMgr::get取资产的情况）
+	pub hits: usize,
+	pub misses: usize,
+	/// 累计整理掉的容量（由整理前后已用容量的差值推算）
+	pub evicted: usize,
+}
+
+impl AssetMgrStats {
+	/// 占用比例（已用容量 / 最大容量）
+	pub fn ratio(&self) -> f32 {
+		if self.max == 0 {
+			return 0.0;
+		}
+		self.size as f32 / self.max as f32
+	}
+}
+
+/// 资产统计，每次整理资产后更新
+#[derive(Debug, Clone, Default, Resource, Serialize)]
+pub struct AssetAccount {
+	pub total_capacity: usize,
+	pub size: usize,
+	/// 整理次数
+	pub collect_count: usize,
+	/// 更新时间
+	pub time: u64,
+	pub mgrs: Vec<AssetMgrStats>,
+}
+
+impl AssetAccount {
+	pub fn get(&self, name: &str) -> Option<&AssetMgrStats> {
+		self.mgrs.iter().find(|r| r.name.as_deref() == Some(name))
+	}
+
+	/// 占用比例（已用容量 / 总容量）
+	pub fn ratio(&self) -> f32 {
+		if self.total_capacity == 0 {
+			return 0.0;
+		}
+		self.size as f32 / self.total_capacity as f32
+	}
+
+	pub fn to_json(&self) -> serde_json::Result<String> {
+		serde_json::to_string(self)
+	}
+}
+
+/// 占用阈值
+#[derive(Debug, Clone)]
+pub struct AssetThreshold {
+	/// 资产类型名，None表示总容量
+	pub name: Option<String>,
+	/// 占用比例
+	pub ratio: f32,
+	// 当前是否超过阈值
+	exceeded: bool,
+}
+
+impl AssetThreshold {
+	pub fn new(name: Option<&str>, ratio: f32) -> Self {
+		Self { name: name.map(|r| r.to_string()), ratio, exceeded: false }
+	}
+}
+
+/// 占用阈值配置
+#[derive(Debug, Clone, Default, Resource)]
+pub struct AssetThresholds(pub Vec<AssetThreshold>);
+
+impl AssetThresholds {
+	pub fn add(&mut self, name: Option<&str>, ratio: f32) -> &mut Self {
+		self.0.push(AssetThreshold::new(name, ratio));
+		self
+	}
+}
+
+/// 占用比例越过阈值（超过，或回落到阈值以下）
+#[derive(Debug, Clone, Event)]
+pub struct AssetThresholdEvent {
+	pub name: Option<String>,
+	pub threshold: f32,
+	pub ratio: f32,
+	/// true为超过阈值，false为回落到阈值以下
+	pub exceeded: bool,
+}
+
+/// 整理后更新资产统计，并检查阈值
+pub fn update_asset_account(
+	allocator: Res<Allocator>,
+	policy: Res<AssetCollectPolicy>,
+	mut account: ResMut<AssetAccount>,
+	mut thresholds: ResMut<AssetThresholds>,
+	mut events: EventWriter<AssetThresholdEvent>,
+) {
+	if account.collect_count == policy.count() && account.time != 0 {
+		return;
+	}
+
+	let mgrs = allocator.stats();
+	account.total_capacity = allocator.total_capacity();
+	account.size = mgrs.iter().map(|r| r.size).sum();
+	account.collect_count = policy.count();
+	account.time = policy.last_time();
+	account.mgrs = mgrs;
+
+	for threshold in thresholds.0.iter_mut() {
+		let ratio = match &threshold.name {
+			Some(name) => match account.get(name) {
+				Some(r) => r.ratio(),
+				None => continue,
+			},
+			None => account.ratio(),
+		};
+		let exceeded = ratio >= threshold.ratio;
+		if exceeded != threshold.exceeded {
+			threshold.exceeded = exceeded;
+			events.send(AssetThresholdEvent {
+				name: threshold.name.clone(),
+				threshold: threshold.ratio,
+				ratio,
+				exceeded,
+			});
+		}
+	}
+}
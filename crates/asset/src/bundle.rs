@@ -0,0 +1,237 @@
+//! 资产预加载清单
+//! 清单将资产路径分组为资产包，AssetBundles::preload预加载整个资产包（按扩展名选择已注册的加载器），
+//! 可以查询加载进度（数量和字节数，字节数取清单中记录的大小），全部加载完成后发出BundleLoaded事件；
+//! 固定（pin）的资产包持有资产的句柄，在release之前不会被整理
+//! ```json
+//! { "bundles": { "scene1": [ { "path": "res/a.png", "size": 1024 }, { "path": "res/b.gltf" } ] } }
+//! ```
+
+use std::path::Path;
+
+use bevy_app::{App, First, Plugin};
+use bevy_ecs::{
+	event::{Event, EventWriter},
+	schedule::IntoSystemConfigs,
+	system::{ResMut, Resource},
+};
+use pi_bevy_ecs_extend::async_system::{AsyncSyncPoint, AsyncTaskRegistry, AsyncTasksPlugin};
+use pi_hash::XHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{AssetConfigError, AssetServer, DynLoadHandle};
+
+/// 清单中的资产
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+	pub path: String,
+	/// 资产大小（字节），用于统计加载进度
+	#[serde(default)]
+	pub size: usize,
+}
+
+/// 资产预加载清单
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetManifest {
+	#[serde(default)]
+	pub bundles: XHashMap<String, Vec<ManifestEntry>>,
+}
+
+impl AssetManifest {
+	pub fn from_json(s: &str) -> Result<Self, AssetConfigError> {
+		serde_json::from_str(s).map_err(|e| AssetConfigError::Parse(e.to_string()))
+	}
+
+	pub fn from_toml(s: &str) -> Result<Self, AssetConfigError> {
+		toml::from_str(s).map_err(|e| AssetConfigError::Parse(e.to_string()))
+	}
+
+	/// 加载清单文件，按扩展名区分格式（.toml为toml，其他为json）
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AssetConfigError> {
+		let path = path.as_ref();
+		let s = std::fs::read_to_string(path).map_err(|e| AssetConfigError::Io(format!("{:?}, {:?}", path, e)))?;
+		match path.extension().and_then(|r| r.to_str()) {
+			Some("toml") => Self::from_toml(&s),
+			_ => Self::from_json(&s),
+		}
+	}
+}
+
+/// 资产包的加载进度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BundleProgress {
+	pub total: usize,
+	pub loaded: usize,
+	pub failed: usize,
+	pub total_bytes: usize,
+	pub loaded_bytes: usize,
+}
+
+impl BundleProgress {
+	/// 是否已全部完成（成功或失败）
+	pub fn is_done(&self) -> bool {
+		self.loaded + self.failed >= self.total
+	}
+
+	/// 按字节数计算的进度（0~1），清单中没有记录大小时按数量计算
+	pub fn ratio(&self) -> f32 {
+		if self.total_bytes > 0 {
+			self.loaded_bytes as f32 / self.total_bytes as f32
+		} else if self.total > 0 {
+			self.loaded as f32 / self.total as f32
+		} else {
+			1.0
+		}
+	}
+}
+
+// 资产包中的资产
+struct BundleItem {
+	path: String,
+	size: usize,
+	// 加载句柄，未固定的资产包在资产加载完成后释放句柄
+	handle: Option<Box<dyn DynLoadHandle>>,
+	// 加载结果，None为加载中，Some(true)为加载失败
+	failed: Option<bool>,
+}
+
+// 预加载中、或已固定的资产包
+struct BundleState {
+	items: Vec<BundleItem>,
+	pin: bool,
+	progress: BundleProgress,
+	// 是否已发出BundleLoaded事件
+	notified: bool,
+}
+
+/// 资产包预加载完成（failed为加载失败的资产数量）
+#[derive(Debug, Clone, Event)]
+pub struct BundleLoaded {
+	pub name: String,
+	pub failed: usize,
+}
+
+/// 资产包
+#[derive(Default, Resource)]
+pub struct AssetBundles {
+	manifest: AssetManifest,
+	bundles: XHashMap<String, BundleState>,
+}
+
+impl AssetBundles {
+	pub fn new(manifest: AssetManifest) -> Self {
+		Self { manifest, bundles: XHashMap::default() }
+	}
+
+	/// 合并清单，同名资产包被替换
+	pub fn add_manifest(&mut self, manifest: AssetManifest) {
+		self.manifest.bundles.extend(manifest.bundles);
+	}
+
+	pub fn manifest(&self) -> &AssetManifest {
+		&self.manifest
+	}
+
+	/// 预加载资产包，pin为true时资产包中的资产在release之前不会被整理；资产包不存在时返回false
+	pub fn preload(&mut self, server: &AssetServer, name: &str, pin: bool) -> bool {
+		let entries = match self.manifest.bundles.get(name) {
+			Some(r) => r,
+			None => {
+				log::warn!("asset bundle is not exist: {:?}", name);
+				return false;
+			}
+		};
+		if let Some(r) = self.bundles.get_mut(name) {
+			// 已预加载的资产包改为固定，重新取到已释放的句柄
+			if pin && !r.pin {
+				r.pin = true;
+				for item in r.items.iter_mut().filter(|r| r.handle.is_none() && r.failed == Some(false)) {
+					item.handle = Some(server.load_dyn(&item.path));
+				}
+			}
+			return true;
+		}
+
+		let items = entries
+			.iter()
+			.map(|r| BundleItem { path: r.path.clone(), size: r.size, handle: Some(server.load_dyn(&r.path)), failed: None })
+			.collect::<Vec<_>>();
+		let progress = BundleProgress {
+			total: items.len(),
+			total_bytes: items.iter().map(|r| r.size).sum(),
+			..Default::default()
+		};
+		self.bundles.insert(name.to_string(), BundleState { items, pin, progress, notified: false });
+		true
+	}
+
+	/// 加载进度，资产包未预加载时返回None
+	pub fn progress(&self, name: &str) -> Option<BundleProgress> {
+		self.bundles.get(name).map(|r| r.progress)
+	}
+
+	pub fn is_pinned(&self, name: &str) -> bool {
+		self.bundles.get(name).map_or(false, |r| r.pin)
+	}
+
+	/// 释放资产包，固定的资产恢复正常整理
+	pub fn release(&mut self, name: &str) -> bool {
+		self.bundles.remove(name).is_some()
+	}
+}
+
+/// 更新资产包的加载进度
+pub fn update_bundles(mut bundles: ResMut<AssetBundles>, mut events: EventWriter<BundleLoaded>) {
+	for (name, bundle) in bundles.bundles.iter_mut() {
+		if bundle.notified {
+			continue;
+		}
+		let mut progress = BundleProgress { total: bundle.progress.total, total_bytes: bundle.progress.total_bytes, ..Default::default() };
+		for item in bundle.items.iter_mut() {
+			if item.failed.is_none() {
+				match &item.handle {
+					Some(r) if r.is_loading() => continue,
+					Some(r) => item.failed = Some(r.is_failed()),
+					None => continue,
+				}
+				// 未固定的资产包，加载完成后不再持有资产
+				if !bundle.pin {
+					item.handle = None;
+				}
+			}
+			if item.failed == Some(true) {
+				progress.failed += 1;
+			} else {
+				progress.loaded += 1;
+				progress.loaded_bytes += item.size;
+			}
+		}
+		bundle.progress = progress;
+		if progress.is_done() {
+			bundle.notified = true;
+			events.send(BundleLoaded { name: name.clone(), failed: progress.failed });
+		}
+	}
+}
+
+/// 资产包插件，需要先注册资产加载器
+#[derive(Default)]
+pub struct AssetBundlePlugin {
+	pub manifest: AssetManifest,
+}
+
+impl AssetBundlePlugin {
+	pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AssetConfigError> {
+		Ok(Self { manifest: AssetManifest::load(path)? })
+	}
+}
+
+impl Plugin for AssetBundlePlugin {
+	fn build(&self, app: &mut App) {
+		if !app.world.contains_resource::<AsyncTaskRegistry>() {
+			app.add_plugins(AsyncTasksPlugin);
+		}
+		app.insert_resource(AssetBundles::new(self.manifest.clone()))
+			.add_event::<BundleLoaded>()
+			.add_systems(First, update_bundles.after(AsyncSyncPoint));
+	}
+}
@@ -0,0 +1,280 @@
+//! 资产依赖
+//! 资产创建时可以记录它依赖的资产（如BindGroup依赖TextureView、Sampler，Pipeline依赖Shader），
+//! 被依赖的资产重新加载、释放或被资产管理器整理掉时，依赖它的资产会被级联地从资产管理器中移除，并发出AssetInvalidated事件；
+//! 仍被引用（Handle存活）的资产无法从资产管理器中移除，它的依赖记录会被保留，也不会发出事件；
+//! 也可以查询某个资产被哪些资产引用（keep_alive）
+
+use std::any::{Any, TypeId};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use bevy_ecs::{
+	event::{Event, EventReader, EventWriter},
+	system::{Local, Res, ResMut, Resource},
+};
+use pi_assets::asset::{Asset, Garbageer};
+use pi_hash::{XHashMap, XHashSet};
+use pi_share::Share;
+
+use crate::{AssetCollectPolicy, AssetReloaded, ShareAssetMgr};
+
+// 类型擦除的资产键
+trait DynKey: Send + Sync {
+	fn as_any(&self) -> &dyn Any;
+	fn eq_key(&self, other: &dyn DynKey) -> bool;
+	fn hash_key(&self, state: &mut dyn Hasher);
+}
+
+impl<K: Hash + Eq + Send + Sync + 'static> DynKey for K {
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn eq_key(&self, other: &dyn DynKey) -> bool {
+		other.as_any().downcast_ref::<K>().map_or(false, |r| r == self)
+	}
+
+	fn hash_key(&self, mut state: &mut dyn Hasher) {
+		self.hash(&mut state);
+	}
+}
+
+/// 资产id，由资产类型和键组成
+#[derive(Clone)]
+pub struct AssetId {
+	pub type_id: TypeId,
+	key: Share<dyn DynKey>,
+}
+
+impl AssetId {
+	pub fn of<A: Asset>(key: &A::Key) -> Self {
+		Self { type_id: TypeId::of::<A>(), key: Share::new(key.clone()) }
+	}
+
+	/// 资产的键，类型不符时返回None
+	pub fn key<A: Asset>(&self) -> Option<&A::Key> {
+		if self.type_id != TypeId::of::<A>() {
+			return None;
+		}
+		self.key.as_any().downcast_ref::<A::Key>()
+	}
+
+	/// 键的hash（用于输出）
+	pub fn key_hash(&self) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		self.key.hash_key(&mut hasher);
+		hasher.finish()
+	}
+}
+
+impl PartialEq for AssetId {
+	fn eq(&self, other: &Self) -> bool {
+		self.type_id == other.type_id && self.key.eq_key(&*other.key)
+	}
+}
+
+impl Eq for AssetId {}
+
+impl Hash for AssetId {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.type_id.hash(state);
+		self.key.hash_key(state);
+	}
+}
+
+impl fmt::Debug for AssetId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("AssetId").field("type_id", &self.type_id).field("key", &format_args!("{:x}", self.key_hash())).finish()
+	}
+}
+
+#[derive(Default)]
+struct DepNode {
+	// 资产类型名
+	ty: &'static str,
+	// 依赖的资产
+	depends: XHashSet<AssetId>,
+	// 依赖该资产的资产
+	dependents: XHashSet<AssetId>,
+	// 将资产从资产管理器中移除，返回资产是否已不在资产管理器中
+	release: Option<Box<dyn Fn() -> bool + Send + Sync>>,
+	// 资产是否仍在资产管理器中
+	present: Option<Box<dyn Fn() -> bool + Send + Sync>>,
+}
+
+/// 资产依赖关系
+#[derive(Default, Resource)]
+pub struct AssetDeps {
+	nodes: XHashMap<AssetId, DepNode>,
+}
+
+impl AssetDeps {
+	/// 记录资产及其依赖的资产
+	pub fn record<A: Asset, G: Garbageer<A>>(
+		&mut self,
+		mgr: &ShareAssetMgr<A, G>,
+		key: &A::Key,
+		depends: impl IntoIterator<Item = AssetId>,
+	) -> AssetId {
+		let id = AssetId::of::<A>(key);
+		let node = self.nodes.entry(id.clone()).or_default();
+		node.ty = std::any::type_name::<A>();
+		let (m, k) = (mgr.clone(), key.clone());
+		node.release = Some(Box::new(move || m.0.remove(&k).is_some() || !m.contains_key(&k)));
+		let (m, k) = (mgr.clone(), key.clone());
+		node.present = Some(Box::new(move || m.contains_key(&k)));
+		for dep in depends {
+			self.add(id.clone(), dep);
+		}
+		id
+	}
+
+	/// 添加依赖：owner依赖dep
+	pub fn add(&mut self, owner: AssetId, dep: AssetId) {
+		if owner == dep {
+			return;
+		}
+		self.nodes.entry(owner.clone()).or_default().depends.insert(dep.clone());
+		self.nodes.entry(dep).or_default().dependents.insert(owner);
+	}
+
+	/// 资产直接依赖的资产
+	pub fn depends(&self, id: &AssetId) -> impl Iterator<Item = &AssetId> + '_ {
+		self.nodes.get(id).into_iter().flat_map(|r| r.depends.iter())
+	}
+
+	/// 直接依赖该资产的资产
+	pub fn dependents(&self, id: &AssetId) -> impl Iterator<Item = &AssetId> + '_ {
+		self.nodes.get(id).into_iter().flat_map(|r| r.dependents.iter())
+	}
+
+	/// 资产类型名（只有通过record记录的资产才有）
+	pub fn type_name(&self, id: &AssetId) -> Option<&'static str> {
+		self.nodes.get(id).map(|r| r.ty).filter(|r| !r.is_empty())
+	}
+
+	/// 使该资产保持存活的所有资产（直接或间接依赖该资产的资产）
+	pub fn keep_alive(&self, id: &AssetId) -> Vec<AssetId> {
+		let mut result = Vec::new();
+		let mut visited = XHashSet::default();
+		let mut stack = vec![id];
+		while let Some(cur) = stack.pop() {
+			for r in self.dependents(cur) {
+				if r != id && visited.insert(r) {
+					result.push(r.clone());
+					stack.push(r);
+				}
+			}
+		}
+		result
+	}
+
+	/// 使依赖该资产的资产失效（级联地从资产管理器中移除），资产自身保留，返回失效的资产
+	/// 仍被引用而无法移除的资产不在返回值中
+	pub fn invalidate(&mut self, id: &AssetId) -> Vec<AssetId> {
+		let dependents = self.keep_alive(id);
+		dependents.into_iter().filter(|r| self.release_node(r)).collect()
+	}
+
+	/// 释放资产，依赖它的资产同样被释放，返回失效的资产（不含该资产自身）
+	pub fn release(&mut self, id: &AssetId) -> Vec<AssetId> {
+		let invalidated = self.invalidate(id);
+		if !self.release_node(id) {
+			log::warn!("release asset fail, asset is still referenced: {:?}, {:?}", self.type_name(id), id);
+		}
+		invalidated
+	}
+
+	/// 删除资产的依赖记录，不释放资产（资产已被资产管理器整理掉时调用）
+	pub fn forget(&mut self, id: &AssetId) {
+		if let Some(node) = self.nodes.remove(id) {
+			for r in node.depends.iter() {
+				if let Some(dep) = self.nodes.get_mut(r) {
+					dep.dependents.remove(id);
+				}
+			}
+			for r in node.dependents.iter() {
+				if let Some(owner) = self.nodes.get_mut(r) {
+					owner.depends.remove(id);
+				}
+			}
+		}
+	}
+
+	/// 清理已不在资产管理器中的资产（被整理或被直接移除），依赖它们的资产级联失效，
+	/// 返回(失效的资产, 导致失效的资产)
+	pub fn prune(&mut self) -> Vec<(AssetId, AssetId)> {
+		let removed: Vec<AssetId> = self
+			.nodes
+			.iter()
+			.filter(|(_, r)| r.present.as_ref().map_or(false, |present| !present()))
+			.map(|(id, _)| id.clone())
+			.collect();
+		let mut result = Vec::new();
+		for id in removed {
+			// 可能已作为其他资产的依赖者被级联释放
+			if !self.nodes.contains_key(&id) {
+				continue;
+			}
+			for r in self.invalidate(&id) {
+				result.push((r, id.clone()));
+			}
+			self.forget(&id);
+		}
+		// 未记录、且已没有依赖关系的节点
+		self.nodes.retain(|_, r| r.present.is_some() || !r.depends.is_empty() || !r.dependents.is_empty());
+		result
+	}
+
+	pub fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	// 从资产管理器中移除资产并删除依赖记录；资产仍被引用、移除失败时保留记录，返回false
+	fn release_node(&mut self, id: &AssetId) -> bool {
+		let released = self.nodes.get(id).and_then(|r| r.release.as_ref()).map_or(true, |release| release());
+		if released {
+			self.forget(id);
+		}
+		released
+	}
+}
+
+/// 资产因依赖的资产重新加载、释放或被整理而失效（已从资产管理器中移除，需要重新创建）
+#[derive(Debug, Clone, Event)]
+pub struct AssetInvalidated {
+	pub id: AssetId,
+	/// 导致失效的资产
+	pub cause: AssetId,
+}
+
+/// 资产重新加载后，使依赖它的资产失效
+pub fn invalidate_reloaded(
+	mut reloaded: EventReader<AssetReloaded>,
+	mut deps: ResMut<AssetDeps>,
+	mut events: EventWriter<AssetInvalidated>,
+) {
+	for e in reloaded.iter() {
+		for id in deps.invalidate(&e.id) {
+			events.send(AssetInvalidated { id, cause: e.id.clone() });
+		}
+	}
+}
+
+/// 资产管理器整理后，清理已被整理掉的资产，并使依赖它们的资产失效
+/// 只在整理次数（AssetCollectPolicy::count）变化的帧检查，直接从资产管理器中移除的资产在下次整理后被清理
+pub fn prune_collected(
+	policy: Res<AssetCollectPolicy>,
+	mut last_count: Local<usize>,
+	mut deps: ResMut<AssetDeps>,
+	mut events: EventWriter<AssetInvalidated>,
+) {
+	if policy.count() == *last_count {
+		return;
+	}
+	*last_count = policy.count();
+	for (id, cause) in deps.prune() {
+		events.send(AssetInvalidated { id, cause });
+	}
+}
@@ -0,0 +1,158 @@
+//! 资产淘汰策略
+//! 资产管理器默认只按超时和容量整理；创建ShareAssetMgr时可以指定淘汰策略（EvictPolicy），
+//! 整理时，已用容量超出分配的容量的部分，按策略给出的分数从小到大淘汰未被引用的资产。
+//! 只有通过ShareAssetMgr::get、insert访问的资产会被记录使用时间。
+//! 固定（PRIORITY_PINNED）和可见只是尽力而为：它们只让淘汰策略跳过该资产，
+//! 未被引用的资产仍会被资产管理器按超时或容量整理掉，需要保证存活的资产应自行持有Handle
+
+use std::hash::Hash;
+
+use pi_assets::{allocator::Collect, asset::{Asset, Garbageer}, mgr::AssetMgr};
+use pi_hash::XHashMap;
+use pi_share::{Share, ShareMutex};
+use pi_time::now_millisecond;
+
+/// 固定的优先级，该优先级的资产不会被淘汰策略淘汰（尽力而为，未被引用时仍可能被资产管理器按超时或容量整理）
+pub const PRIORITY_PINNED: i32 = i32::MAX;
+
+/// 资产的使用信息
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EvictInfo {
+	/// 最后使用时间
+	pub last_use: u64,
+	/// 优先级，越大越晚被淘汰
+	pub priority: i32,
+	/// 是否可见，可见的资产不会被淘汰策略淘汰（尽力而为，同PRIORITY_PINNED）
+	pub visible: bool,
+}
+
+/// 淘汰策略
+pub trait EvictPolicy: Send + Sync + 'static {
+	/// 资产的淘汰分数，分数越小越先被淘汰，None表示不淘汰
+	fn score(&self, info: &EvictInfo, now: u64) -> Option<u64>;
+}
+
+/// 超时策略，只由资产管理器按超时整理
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeoutPolicy;
+
+impl EvictPolicy for TimeoutPolicy {
+	fn score(&self, _info: &EvictInfo, _now: u64) -> Option<u64> {
+		None
+	}
+}
+
+/// 最近最少使用策略
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LruPolicy;
+
+impl EvictPolicy for LruPolicy {
+	fn score(&self, info: &EvictInfo, _now: u64) -> Option<u64> {
+		if info.visible {
+			return None;
+		}
+		Some(info.last_use)
+	}
+}
+
+/// 优先级策略，每级优先级相当于最后使用时间推后weight毫秒
+#[derive(Debug, Clone, Copy)]
+pub struct PriorityPolicy {
+	pub weight: u64,
+}
+
+impl Default for PriorityPolicy {
+	fn default() -> Self {
+		Self { weight: 10 * 1000 }
+	}
+}
+
+impl EvictPolicy for PriorityPolicy {
+	fn score(&self, info: &EvictInfo, _now: u64) -> Option<u64> {
+		if info.visible || info.priority == PRIORITY_PINNED {
+			return None;
+		}
+		let offset = info.priority.unsigned_abs() as u64 * self.weight;
+		Some(if info.priority >= 0 { info.last_use + offset } else { info.last_use.saturating_sub(offset) })
+	}
+}
+
+fn new_info() -> EvictInfo {
+	EvictInfo { last_use: now_millisecond(), ..Default::default() }
+}
+
+/// 按淘汰策略整理资产（由容量分配器在整理前调用）
+pub(crate) trait Evict: Send + Sync {
+	/// 淘汰资产，直到已用容量不超过分配的容量
+	fn evict(&self, now: u64);
+}
+
+/// 资产管理器的淘汰记录
+pub struct Evictor<A: Asset, G: Garbageer<A>> {
+	mgr: Share<AssetMgr<A, G>>,
+	policy: Box<dyn EvictPolicy>,
+	infos: ShareMutex<XHashMap<A::Key, EvictInfo>>,
+}
+
+impl<A: Asset, G: Garbageer<A>> Evictor<A, G>
+where
+	A::Key: Hash + Eq + Clone,
+{
+	pub(crate) fn new(mgr: Share<AssetMgr<A, G>>, policy: Box<dyn EvictPolicy>) -> Self {
+		Self { mgr, policy, infos: ShareMutex::new(XHashMap::default()) }
+	}
+
+	/// 记录资产的使用
+	pub fn touch(&self, k: &A::Key, now: u64) {
+		let mut infos = self.infos.lock();
+		match infos.get_mut(k) {
+			Some(r) => r.last_use = now,
+			None => {
+				infos.insert(k.clone(), EvictInfo { last_use: now, ..Default::default() });
+			}
+		}
+	}
+
+	/// 设置资产的优先级
+	pub fn set_priority(&self, k: &A::Key, priority: i32) {
+		self.infos.lock().entry(k.clone()).or_insert_with(new_info).priority = priority;
+	}
+
+	/// 设置资产是否可见（可见期间不会被淘汰策略淘汰，但不保证资产存活）
+	pub fn set_visible(&self, k: &A::Key, visible: bool) {
+		self.infos.lock().entry(k.clone()).or_insert_with(new_info).visible = visible;
+	}
+
+	pub fn info(&self, k: &A::Key) -> Option<EvictInfo> {
+		self.infos.lock().get(k).copied()
+	}
+}
+
+impl<A: Asset, G: Garbageer<A>> Evict for Evictor<A, G>
+where
+	A::Key: Hash + Eq + Clone,
+	AssetMgr<A, G>: Collect,
+{
+	fn evict(&self, now: u64) {
+		let mut infos = self.infos.lock();
+		// 清理已被资产管理器整理掉的资产的记录
+		infos.retain(|k, _| self.mgr.contains_key(k));
+		if self.mgr.size() <= self.mgr.capacity() {
+			return;
+		}
+		let mut list: Vec<(u64, A::Key)> = infos
+			.iter()
+			.filter_map(|(k, info)| self.policy.score(info, now).map(|score| (score, k.clone())))
+			.collect();
+		list.sort_by_key(|r| r.0);
+		for (_, k) in list {
+			if self.mgr.size() <= self.mgr.capacity() {
+				break;
+			}
+			// 被引用的资产不会被移除
+			if self.mgr.remove(&k).is_some() {
+				infos.remove(&k);
+			}
+		}
+	}
+}
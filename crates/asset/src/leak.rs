@@ -0,0 +1,128 @@
+//! 资产泄漏检测（leak_detect特性）
+//! 记录通过ShareAssetMgr::insert、get创建或取到的资产的创建位置（类型、键、帧、可选的调用栈），
+//! leak_report输出长时间存活、且仍被引用的资产，按大小从大到小排序。
+//! 调用栈只在设置了RUST_BACKTRACE环境变量时记录
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use pi_assets::asset::{Asset, Handle, Size};
+use pi_hash::XHashMap;
+use pi_share::{Share, ShareMutex};
+use serde::Serialize;
+
+use crate::AssetId;
+
+// 当前帧
+static FRAME: AtomicU64 = AtomicU64::new(0);
+
+struct LeakRecord {
+	ty: &'static str,
+	frame: u64,
+	size: usize,
+	backtrace: Option<String>,
+	// 当前引用计数
+	refs: Box<dyn Fn() -> usize + Send + Sync>,
+}
+
+fn records() -> &'static ShareMutex<XHashMap<AssetId, LeakRecord>> {
+	static RECORDS: OnceLock<ShareMutex<XHashMap<AssetId, LeakRecord>>> = OnceLock::new();
+	RECORDS.get_or_init(|| ShareMutex::new(XHashMap::default()))
+}
+
+/// 记录资产的创建位置，已记录的资产不重复记录
+pub(crate) fn record<A: Asset + Size>(key: &A::Key, handle: &Handle<A>) {
+	let id = AssetId::of::<A>(key);
+	let mut records = records().lock();
+	if records.contains_key(&id) {
+		return;
+	}
+	let backtrace = Backtrace::capture();
+	let weak = Share::downgrade(handle);
+	records.insert(id, LeakRecord {
+		ty: std::any::type_name::<A>(),
+		frame: FRAME.load(Ordering::Relaxed),
+		size: handle.size(),
+		backtrace: match backtrace.status() {
+			BacktraceStatus::Captured => Some(backtrace.to_string()),
+			_ => None,
+		},
+		refs: Box::new(move || weak.strong_count()),
+	});
+}
+
+/// 推进帧，并删除已不再被引用的资产的记录
+pub fn update_leak_frame() {
+	FRAME.fetch_add(1, Ordering::Relaxed);
+	records().lock().retain(|_, r| (r.refs)() > 0);
+}
+
+/// 长时间存活的资产
+#[derive(Debug, Clone, Serialize)]
+pub struct LeakEntry {
+	pub ty: &'static str,
+	/// 键的hash
+	pub key: u64,
+	/// 创建时的帧
+	pub frame: u64,
+	/// 已存活的帧数
+	pub age: u64,
+	pub size: usize,
+	/// 引用计数
+	pub refs: usize,
+	pub backtrace: Option<String>,
+}
+
+/// 资产泄漏报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LeakReport {
+	pub frame: u64,
+	pub size: usize,
+	pub entries: Vec<LeakEntry>,
+}
+
+impl LeakReport {
+	pub fn to_json(&self) -> serde_json::Result<String> {
+		serde_json::to_string(self)
+	}
+}
+
+/// 存活超过min_age帧、且仍被引用的资产，按大小从大到小排序
+pub fn leak_report(min_age: u64) -> LeakReport {
+	let frame = FRAME.load(Ordering::Relaxed);
+	let mut entries: Vec<LeakEntry> = records()
+		.lock()
+		.iter()
+		.filter(|(_, r)| frame.saturating_sub(r.frame) >= min_age)
+		.filter_map(|(id, r)| {
+			let refs = (r.refs)();
+			if refs == 0 {
+				return None;
+			}
+			Some(LeakEntry {
+				ty: r.ty,
+				key: id.key_hash(),
+				frame: r.frame,
+				age: frame.saturating_sub(r.frame),
+				size: r.size,
+				refs,
+				backtrace: r.backtrace.clone(),
+			})
+		})
+		.collect();
+	entries.sort_by(|a, b| b.size.cmp(&a.size));
+	LeakReport { frame, size: entries.iter().map(|r| r.size).sum(), entries }
+}
+
+/// 打印资产泄漏报告
+pub fn dump_leak_report(min_age: u64) {
+	let report = leak_report(min_age);
+	log::warn!("asset leak report, frame: {}, count: {}, size: {}", report.frame, report.entries.len(), report.size);
+	for r in report.entries.iter() {
+		log::warn!("  {} key: {:x}, size: {}, refs: {}, frame: {}, age: {}", r.ty, r.key, r.size, r.refs, r.frame, r.age);
+		if let Some(backtrace) = &r.backtrace {
+			log::warn!("{}", backtrace);
+		}
+	}
+}
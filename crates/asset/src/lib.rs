@@ -1,86 +1,709 @@
 use std::any::TypeId;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use bevy_ecs::system::{Resource, ResMut, Local};
-use bevy_app::prelude::{App, Plugin, Last};
-use pi_assets::{mgr::AssetMgr, asset::{GarbageEmpty, Asset, Garbageer, Size}, homogeneous::HomogeneousMgr};
-use pi_hash::XHashMap;
+use bevy_ecs::system::{Resource, Res, ResMut};
+use bevy_ecs::event::{Event, EventReader};
+use bevy_ecs::schedule::IntoSystemConfigs;
+#[cfg(feature="account_info")]
+use bevy_ecs::system::Local;
+use bevy_app::prelude::{App, Plugin, First, Last};
+use pi_assets::allocator::Collect;
+use pi_assets::{mgr::AssetMgr, asset::{GarbageEmpty, Asset, Garbageer, Handle, Size}, homogeneous::HomogeneousMgr};
+use pi_hash::{XHashMap, XHashSet};
 use pi_render::renderer::bind_group::{BindGroup, BindGroupLayout};
 use pi_render::renderer::sampler::SamplerRes;
 use pi_render::renderer::texture::{ImageTexture, ImageTextureView};
 use pi_render::renderer::vertex_buffer::EVertexBufferRange;
-use pi_render::rhi::asset::{TextureRes, RenderRes};
+use pi_render::rhi::asset::{TextureRes, RenderRes, AssetWithId};
 use pi_render::rhi::pipeline::RenderPipeline;
-use pi_share::Share;
+use pi_render::rhi::{bind_group::BindGroup as RenderBindGroup, buffer::Buffer};
+use pi_render::components::view::target_alloc::UnuseTexture;
+use wgpu::TextureView;
+use pi_share::{Share, ShareMutex};
 use serde::{Serialize, Deserialize};
 use pi_time::now_millisecond;
 use pi_null::Null;
 use derive_deref::{Deref, DerefMut};
 
-/// 资产功能插件， 负责添加容量分配器`Allocator`作为单例， 添加容量配置单例`AssetConfig`, 添加system `collect`负责定时整理资产
+pub use pi_bevy_asset_macro::AssetCapacity;
+
+mod account;
+mod bundle;
+mod depend;
+mod evict;
+mod loader;
+#[cfg(feature="leak_detect")]
+pub mod leak;
+pub use account::*;
+pub use bundle::*;
+pub use depend::*;
+pub use evict::*;
+pub use loader::*;
+
+/// 资产功能插件， 负责添加容量分配器`Allocator`作为单例， 添加容量配置单例`AssetConfig`, 添加system `collect`负责按整理策略`AssetCollectPolicy`整理资产
 pub struct PiAssetPlugin {
 	pub total_capacity: usize,
 	pub asset_config: AssetConfig,
+	pub collect_policy: AssetCollectPolicy,
+	/// 配置文件的内容，会覆盖total_capacity和asset_config中的同名配置
+	pub config_file: Option<AssetConfigFile>,
 }
 impl Default for PiAssetPlugin {
 	fn default() -> Self {
-		Self { total_capacity: 32 * 1024 * 1024, asset_config: AssetConfig::default() }
+		Self { total_capacity: 32 * 1024 * 1024, asset_config: AssetConfig::default(), collect_policy: AssetCollectPolicy::default(), config_file: None }
+	}
+}
+
+impl PiAssetPlugin {
+	/// 用配置文件（json或toml）创建插件
+	/// 配置文件中min大于max时返回错误；类型名要等所有插件注册后才能校验，未注册的类型名会使插件在finish时panic
+	pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AssetConfigError> {
+		let file = AssetConfigFile::load(path)?;
+		let errors: Vec<String> = file
+			.assets
+			.iter()
+			.filter(|(_, desc)| desc.min > desc.max)
+			.map(|(name, _)| format!("min > max, asset type: {:?}", name))
+			.collect();
+		if errors.len() > 0 {
+			return Err(AssetConfigError::Invalid(errors));
+		}
+		Ok(Self { config_file: Some(file), ..Default::default() })
 	}
 }
 
 impl Plugin for PiAssetPlugin {
     fn build(&self, app: &mut App) {
-		let total_capacity = if self.total_capacity.is_null() || self.total_capacity == 0 {
+		let mut total_capacity = if self.total_capacity.is_null() || self.total_capacity == 0 {
 			32 * 1024 * 1024 // 默认32M
 		} else {
 			self.total_capacity
 		};
-		app.insert_resource(Allocator(pi_assets::allocator::Allocator::new(total_capacity)));
-		app.insert_resource(self.asset_config.clone());
+		let mut asset_config = self.asset_config.clone();
+		register_asset_names(&mut asset_config);
+		if let Some(file) = &self.config_file {
+			if let Some(r) = file.total_capacity {
+				total_capacity = r;
+			}
+			for (name, desc) in file.assets.iter() {
+				asset_config.insert_by_name(name.clone(), desc.clone());
+			}
+		}
+		app.insert_resource(Allocator::new(total_capacity));
+		app.insert_resource(asset_config);
+		app.insert_resource(self.collect_policy.clone());
 
 		// 帧推结束前，整理资产（这里采用在帧推结束前整理资产， 而不是利用容量分配器自带的定时整理， 可以防止整理立即打断正在进行的其他system）
-		app.add_systems(Last, collect);
+		app.add_systems(First, begin_collect_frame);
+		app.init_resource::<AssetAccount>();
+		app.init_resource::<AssetThresholds>();
+		app.add_event::<AssetThresholdEvent>();
+		app.add_event::<AssetMemoryPressure>();
+		if !app.world.contains_resource::<AssetDeps>() {
+			app.init_resource::<AssetDeps>().add_event::<AssetInvalidated>();
+		}
+		app.add_systems(Last, (apply_asset_config, handle_memory_pressure, collect, prune_collected, update_asset_account).chain());
 
 		#[cfg(feature="account_info")]
 		app.add_systems(Last, account);
+
+		#[cfg(feature="leak_detect")]
+		app.add_systems(First, leak::update_leak_frame);
+	}
+
+	fn finish(&self, app: &mut App) {
+		// 所有插件都已注册了资产类型名，校验配置；配置错误（如类型名拼写错误）会使配置静默失效，直接panic
+		if let Err(e) = app.world.resource::<AssetConfig>().validate() {
+			panic!("{}", e);
+		}
 	}
 }
 
 // 上次容量整理时间
+#[deprecated(note = "use `AssetCollectPolicy`")]
 pub struct LastCollectTime(pub u64);
+#[allow(deprecated)]
 impl Default for LastCollectTime {
     fn default() -> Self {
         Self(now_millisecond())
     }
 }
 
+/// 资产整理策略
+/// 距上次整理超过interval时整理；设置了帧预算时，本帧已用时间超过预算则推迟整理（最多推迟一个interval）；
+/// 分配器占用超过pressure比例（默认不启用，资产管理器稳定时的占用通常已接近总容量）、或调用force时，立即整理
+#[derive(Debug, Clone, Resource)]
+pub struct AssetCollectPolicy {
+	/// 整理间隔（毫秒），0表示每帧整理
+	pub interval: u64,
+	/// 帧时间预算（毫秒），None表示不限制
+	pub frame_budget: Option<u64>,
+	/// 触发立即整理的占用比例（已用容量 / 总容量），None表示不按占用整理
+	pub pressure: Option<f32>,
+	force: bool,
+	last_time: u64,
+	frame_start: u64,
+	last_cost: u64,
+	count: usize,
+}
+
+impl Default for AssetCollectPolicy {
+	fn default() -> Self {
+		Self {
+			interval: 1000,
+			frame_budget: None,
+			pressure: None,
+			force: false,
+			last_time: now_millisecond(),
+			frame_start: 0,
+			last_cost: 0,
+			count: 0,
+		}
+	}
+}
+
+impl AssetCollectPolicy {
+	pub fn new(interval: u64) -> Self {
+		Self { interval, ..Default::default() }
+	}
+
+	pub fn with_frame_budget(mut self, budget: u64) -> Self {
+		self.frame_budget = Some(budget);
+		self
+	}
+
+	pub fn with_pressure(mut self, pressure: f32) -> Self {
+		self.pressure = Some(pressure);
+		self
+	}
+
+	/// 要求在本帧结束前整理一次
+	pub fn force(&mut self) {
+		self.force = true;
+	}
+
+	/// 上次整理的时间
+	pub fn last_time(&self) -> u64 {
+		self.last_time
+	}
+
+	/// 上次整理的耗时（毫秒）
+	pub fn last_cost(&self) -> u64 {
+		self.last_cost
+	}
+
+	/// 整理次数
+	pub fn count(&self) -> usize {
+		self.count
+	}
+
+	/// 当前是否应该整理
+	pub fn should_collect(&self, now: u64, pressure: f32) -> bool {
+		if self.force {
+			return true;
+		}
+		if let Some(limit) = self.pressure {
+			if pressure >= limit {
+				return true;
+			}
+		}
+		let elapsed = now.saturating_sub(self.last_time);
+		if elapsed < self.interval {
+			return false;
+		}
+		match self.frame_budget {
+			// 本帧已超出预算，推迟整理， 但最多推迟一个间隔
+			Some(budget) if now.saturating_sub(self.frame_start) > budget => elapsed >= self.interval * 2,
+			_ => true,
+		}
+	}
+
+	fn finish(&mut self, start: u64, end: u64) {
+		self.force = false;
+		self.last_time = end;
+		self.last_cost = end.saturating_sub(start);
+		self.count += 1;
+	}
+}
+
+/// 记录帧开始时间，用于帧预算的判断
+pub fn begin_collect_frame(mut policy: ResMut<AssetCollectPolicy>) {
+	policy.frame_start = now_millisecond();
+}
+
 /// 整理容量
-pub fn collect(mut allocator: ResMut<Allocator>, last_collect_time: Local<LastCollectTime>) {
-	// 暂时设置为每秒整理， 这里间隔配置？TODO
-	if now_millisecond() - last_collect_time.0 > 1000 {
-		allocator.collect(now_millisecond())
+pub fn collect(mut allocator: ResMut<Allocator>, mut policy: ResMut<AssetCollectPolicy>) {
+	let now = now_millisecond();
+	if !policy.should_collect(now, allocator.pressure()) {
+		return;
+	}
+	allocator.collect(now);
+	policy.finish(now, now_millisecond());
+}
+
+/// 内存压力事件，平台层在系统内存不足时发送，立即整理资产
+#[derive(Debug, Clone, Event)]
+pub enum AssetMemoryPressure {
+	/// 整理到总容量的一半
+	Warning,
+	/// 整理到指定容量
+	CollectTo(usize),
+	/// 修改总容量，并按新的总容量整理
+	SetTotalCapacity(usize),
+}
+
+/// 处理内存压力事件
+pub fn handle_memory_pressure(
+	mut events: EventReader<AssetMemoryPressure>,
+	mut allocator: ResMut<Allocator>,
+	mut policy: ResMut<AssetCollectPolicy>,
+) {
+	for e in events.iter() {
+		let now = now_millisecond();
+		match e {
+			AssetMemoryPressure::Warning => {
+				let target = allocator.total_capacity() / 2;
+				allocator.collect_to(target, now);
+			}
+			AssetMemoryPressure::CollectTo(target) => allocator.collect_to(*target, now),
+			AssetMemoryPressure::SetTotalCapacity(total) => {
+				allocator.set_total_capacity(*total);
+				allocator.collect(now);
+			}
+		}
+		log::warn!("asset memory pressure: {:?}, size: {}", e, allocator.size());
+		policy.finish(now, now_millisecond());
+	}
+}
+
+/// 资产配置修改后（如切换画质档位），将新的容量配置应用到已注册的资产管理器上
+pub fn apply_asset_config(config: Res<AssetConfig>, mut allocator: ResMut<Allocator>) {
+	if !config.is_changed() || config.is_added() {
+		return;
+	}
+	if let Err(e) = allocator.apply_config(&config) {
+		log::error!("{}", e);
 	}
 }
 
 /// 容量分配器
-#[derive(Resource, Deref, DerefMut)]
-pub struct Allocator(pub pi_assets::allocator::Allocator);
+/// 通过该类型注册的资产管理器，会被统计到已用容量中，并且可以在运行时修改容量配置；
+/// 需要用Allocator::new创建（不能再用Allocator(..)直接构造）
+///
+/// 内部分配器（self.0）不支持修改已注册的配置，运行时的配置（min、max、总容量）在内部分配器分配容量后，
+/// 直接调整到已注册的资产管理器上；直接注册到内部分配器上的资产管理器（如渲染库初始化时注册的）不受运行时配置影响，但会一直被整理
+#[derive(Resource)]
+pub struct Allocator(pub pi_assets::allocator::Allocator, AllocatorInfo);
+
+impl std::ops::Deref for Allocator {
+	type Target = pi_assets::allocator::Allocator;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl std::ops::DerefMut for Allocator {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+#[derive(Default)]
+struct AllocatorInfo {
+	// 创建内部分配器时的总容量
+	base_capacity: usize,
+	total_capacity: usize,
+	mgrs: Vec<MgrEntry>,
+}
+
+// 已注册的资产管理器
+struct MgrEntry {
+	name: Option<String>,
+	mgr: Share<dyn Collect>,
+	// 内部分配器按注册时的配置、总容量分配到的容量（每次整理后更新）
+	alloc: usize,
+	min: usize,
+	max: usize,
+	// 创建资产管理器时的超时时间，None为未知
+	timeout: Option<usize>,
+	counter: Option<Share<AssetCounter>>,
+	// 资产数量
+	count: Option<Box<dyn Fn() -> usize + Send + Sync>>,
+	// 淘汰策略
+	evictor: Option<Share<dyn Evict>>,
+	// 累计整理掉的容量
+	evicted: usize,
+}
+
+impl Allocator {
+	pub fn new(total_capacity: usize) -> Self {
+		Self(
+			pi_assets::allocator::Allocator::new(total_capacity),
+			AllocatorInfo { base_capacity: total_capacity, total_capacity, mgrs: Vec::new() },
+		)
+	}
+
+	/// 注册资产管理器
+	pub fn register<T: Collect + 'static>(&mut self, mgr: Share<T>, min_capacity: usize, max_capacity: usize) {
+		self.register_named(None, mgr, min_capacity, max_capacity);
+	}
+
+	/// 以资产类型名注册资产管理器，之后可以按类型名修改容量配置
+	pub fn register_named<T: Collect + 'static>(&mut self, name: Option<&str>, mgr: Share<T>, min_capacity: usize, max_capacity: usize) {
+		self.register_entry(name, mgr, min_capacity, max_capacity, None, None, None, None);
+	}
 
-/// 资产配置
+	fn register_entry<T: Collect + 'static>(
+		&mut self,
+		name: Option<&str>,
+		mgr: Share<T>,
+		min_capacity: usize,
+		max_capacity: usize,
+		timeout: Option<usize>,
+		counter: Option<Share<AssetCounter>>,
+		count: Option<Box<dyn Fn() -> usize + Send + Sync>>,
+		evictor: Option<Share<dyn Evict>>,
+	) {
+		self.1.mgrs.push(MgrEntry {
+			name: name.map(|r| r.to_string()),
+			mgr: mgr.clone(),
+			alloc: mgr.capacity(),
+			min: min_capacity,
+			max: max_capacity,
+			timeout,
+			counter,
+			count,
+			evictor,
+			evicted: 0,
+		});
+		self.0.register(mgr, min_capacity, max_capacity);
+	}
+
+	/// 修改某类资产管理器的容量配置，返回是否有修改
+	pub fn reconfigure(&mut self, name: &str, min_capacity: usize, max_capacity: usize) -> bool {
+		let mut changed = false;
+		for r in self.1.mgrs.iter_mut() {
+			if r.name.as_deref() == Some(name) && (r.min != min_capacity || r.max != max_capacity) {
+				r.min = min_capacity;
+				r.max = max_capacity;
+				changed = true;
+			}
+		}
+		if changed {
+			self.adjust_capacity();
+		}
+		changed
+	}
+
+	/// 按资产配置修改所有已命名的资产管理器的容量配置，返回是否有修改
+	/// 超时时间只在创建资产管理器时生效，配置修改了已创建的资产管理器的超时时间时，返回错误，整个配置都不会被应用
+	pub fn apply_config(&mut self, config: &AssetConfig) -> Result<bool, AssetConfigError> {
+		let errors: Vec<String> = self.1.mgrs.iter().filter_map(|r| {
+			let name = r.name.as_ref()?;
+			let timeout = r.timeout?;
+			let desc = config.get_by_name(name)?;
+			if desc.timeout == timeout {
+				return None;
+			}
+			Some(format!("timeout can not be changed at runtime, asset type: {:?}, timeout: {} -> {}", name, timeout, desc.timeout))
+		}).collect();
+		if errors.len() > 0 {
+			return Err(AssetConfigError::Invalid(errors));
+		}
+
+		let mut changed = false;
+		for r in self.1.mgrs.iter_mut() {
+			let desc = match r.name.as_ref().and_then(|name| config.get_by_name(name)) {
+				Some(r) => r,
+				None => continue,
+			};
+			if r.min != desc.min || r.max != desc.max {
+				r.min = desc.min;
+				r.max = desc.max;
+				changed = true;
+			}
+		}
+		if changed {
+			self.adjust_capacity();
+		}
+		Ok(changed)
+	}
+
+	/// 整理资产，并统计每个资产管理器被整理掉的容量
+	pub fn collect(&mut self, now: u64) {
+		let sizes: Vec<usize> = self.1.mgrs.iter().map(|r| r.mgr.size()).collect();
+		// 先按淘汰策略淘汰超出容量的资产
+		for r in self.1.mgrs.iter() {
+			if let Some(evictor) = &r.evictor {
+				evictor.evict(now);
+			}
+		}
+		self.0.collect(now);
+		for r in self.1.mgrs.iter_mut() {
+			r.alloc = r.mgr.capacity();
+		}
+		self.adjust_capacity();
+		for (r, size) in self.1.mgrs.iter_mut().zip(sizes.into_iter()) {
+			r.evicted += size.saturating_sub(r.mgr.size());
+		}
+	}
+
+	/// 修改总容量，已注册的资产管理器分配到的容量按比例缩放（min、max仍然有效）
+	pub fn set_total_capacity(&mut self, total_capacity: usize) {
+		if total_capacity == self.1.total_capacity {
+			return;
+		}
+		self.1.total_capacity = total_capacity;
+		self.adjust_capacity();
+	}
+
+	/// 强制整理，直到已注册的资产管理器的已用容量不超过target（引用中的资产不会被释放，因此不保证一定能达到）
+	/// 临时将各资产管理器的容量按已用容量的比例降低，整理掉超出的未引用资产后恢复原容量
+	pub fn collect_to(&mut self, target: usize, now: u64) {
+		let size = self.size();
+		if size <= target {
+			return;
+		}
+		for r in self.1.mgrs.iter_mut() {
+			let (capacity, old_size) = (r.mgr.capacity(), r.mgr.size());
+			let limit = (old_size as u128 * target as u128 / size as u128) as usize;
+			r.mgr.set_capacity(limit);
+			// 先按淘汰策略淘汰
+			if let Some(evictor) = &r.evictor {
+				evictor.evict(now);
+			}
+			r.mgr.capacity_collect(limit);
+			r.mgr.set_capacity(capacity);
+			r.evicted += old_size.saturating_sub(r.mgr.size());
+		}
+	}
+
+	/// 已注册的资产管理器的统计信息
+	pub fn stats(&self) -> Vec<AssetMgrStats> {
+		self.1.mgrs.iter().map(|r| AssetMgrStats {
+			name: r.name.clone(),
+			size: r.mgr.size(),
+			count: r.count.as_ref().map(|r| r()),
+			capacity: r.mgr.capacity(),
+			min: r.min,
+			max: r.max,
+			hits: r.counter.as_ref().map_or(0, |r| r.hits()),
+			misses: r.counter.as_ref().map_or(0, |r| r.misses()),
+			evicted: r.evicted,
+		}).collect()
+	}
+
+	/// 已注册的资产管理器的类型名和容量配置(min, max)
+	pub fn configs(&self) -> impl Iterator<Item = (Option<&str>, usize, usize)> {
+		self.1.mgrs.iter().map(|r| (r.name.as_deref(), r.min, r.max))
+	}
+
+	// 内部分配器只按注册时的配置分配容量，按运行时的配置（min、max、总容量）调整资产管理器分配到的容量，
+	// 总是从内部分配器分配到的容量计算（多次修改不会累积），容量变小时，立即整理超出的部分
+	fn adjust_capacity(&self) {
+		let (total, base) = (self.1.total_capacity, self.1.base_capacity);
+		for r in self.1.mgrs.iter() {
+			let mut capacity = r.alloc;
+			if total != base && base > 0 {
+				capacity = (capacity as u128 * total as u128 / base as u128) as usize;
+			}
+			let capacity = capacity.min(r.max).max(r.min.min(r.max));
+			r.mgr.set_capacity(capacity);
+			if r.mgr.size() > capacity {
+				r.mgr.capacity_collect(capacity);
+			}
+		}
+	}
+
+	/// 总容量
+	pub fn total_capacity(&self) -> usize {
+		self.1.total_capacity
+	}
+
+	/// 已注册的资产管理器的已用容量
+	pub fn size(&self) -> usize {
+		self.1.mgrs.iter().map(|r| r.mgr.size()).sum()
+	}
+
+	/// 占用比例
+	pub fn pressure(&self) -> f32 {
+		if self.1.total_capacity == 0 {
+			return 0.0;
+		}
+		self.size() as f32 / self.1.total_capacity as f32
+	}
+}
+
+/// 资产配置（资产类型注册表）
+/// 所有资产类型以稳定的类型名为键，配置文件、TAssetCapacity、渲染库的AssetCfg*单例都从这里取容量配置；
+/// 运行时修改配置后，已创建的资产管理器的min、max会在帧末调整到容量分配器上（timeout只在创建资产管理器时生效，运行时修改timeout的配置会被拒绝）
 #[derive(Debug, Clone, Resource, Default)]
-pub struct AssetConfig (XHashMap<TypeId, AssetDesc>);
+pub struct AssetConfig {
+	descs: XHashMap<String, AssetDesc>,
+	// 类型对应的类型名
+	types: XHashMap<TypeId, String>,
+	// 所有已注册的类型名（部分类型名没有对应的rust类型，如渲染库的AssetCfg*）
+	names: XHashSet<String>,
+}
 
 impl AssetConfig {
-	// 为某类型的资产管理器配置容量和超时时间
+	// 为某类型的资产管理器配置容量和超时时间（类型未注册类型名时，以rust类型名作为类型名）
 	#[inline]
     pub fn insert<T: Size>(&mut self, cfg: AssetDesc) {
-        self.0.insert(std::any::TypeId::of::<T>(), cfg);
+		let name = match self.types.get(&TypeId::of::<T>()) {
+			Some(r) => r.clone(),
+			None => {
+				let name = std::any::type_name::<T>();
+				self.register_name::<T>(name);
+				name.to_string()
+			}
+		};
+        self.descs.insert(name, cfg);
     }
 
+	// 按资产类型名配置容量和超时时间
+	#[inline]
+	pub fn insert_by_name(&mut self, name: String, cfg: AssetDesc) {
+		self.descs.insert(name, cfg);
+	}
+
+	// 注册资产类型名
+	pub fn register_name<T: 'static>(&mut self, name: &str) {
+		if let Some(old) = self.types.insert(TypeId::of::<T>(), name.to_string()) {
+			// 之前以其他类型名配置的，转移到新的类型名上
+			if old != name {
+				if let Some(desc) = self.descs.remove(&old) {
+					self.descs.entry(name.to_string()).or_insert(desc);
+				}
+			}
+		}
+		self.names.insert(name.to_string());
+	}
+
+	// 注册没有对应rust类型的资产类型名
+	pub fn register_key(&mut self, name: &str) {
+		self.names.insert(name.to_string());
+	}
+
+	// 注册TAssetCapacity类型
+	pub fn register<T: TAssetCapacity + 'static>(&mut self) {
+		self.register_name::<T>(T::ASSET_TYPE);
+	}
+
+	// 资产类型名
+	pub fn name<T: 'static>(&self) -> Option<&str> {
+		self.types.get(&TypeId::of::<T>()).map(|r| r.as_str())
+	}
+
 	// 取到某类型的资产管理器的容量、超时配置
 	#[inline]
     pub fn get<T: Size>(&self) -> Option<&AssetDesc> {
-		self.0.get(&std::any::TypeId::of::<T>())
+		self.types.get(&TypeId::of::<T>()).and_then(|name| self.descs.get(name))
     }
+
+	// 按类型名取到容量、超时配置
+	#[inline]
+	pub fn get_by_name(&self, name: &str) -> Option<&AssetDesc> {
+		self.descs.get(name)
+	}
+
+	// 取到TAssetCapacity类型的容量配置，未配置时为类型的默认配置
+	pub fn capacity<T: TAssetCapacity>(&self) -> AssetCapacity {
+		self.capacity_by_name(T::ASSET_TYPE, T::capacity())
+	}
+
+	// 取到TAssetCapacity类型的容量配置，未配置时注册类型名，并写入类型的默认配置（替代原AssetMgrConfigs::query）
+	pub fn query<T: TAssetCapacity + 'static>(&mut self) -> AssetCapacity {
+		self.register::<T>();
+		self.descs.entry(T::ASSET_TYPE.to_string()).or_insert_with(|| T::capacity().into()).clone().into()
+	}
+
+	// 按类型名取到容量配置，未配置时为default
+	pub fn capacity_by_name(&self, name: &str, default: AssetCapacity) -> AssetCapacity {
+		self.descs.get(name).map_or(default, |r| r.clone().into())
+	}
+
+	// 所有的配置
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &AssetDesc)> {
+		self.descs.iter().map(|(k, v)| (k.as_str(), v))
+	}
+
+	// 所有已注册的类型名
+	pub fn names(&self) -> impl Iterator<Item = &str> {
+		self.names.iter().map(|r| r.as_str())
+	}
+
+	/// 校验配置：类型名必须已注册，min不能大于max
+	pub fn validate(&self) -> Result<(), AssetConfigError> {
+		let mut errors = Vec::new();
+		for (name, desc) in self.descs.iter() {
+			if !self.names.contains(name) {
+				errors.push(format!("unknown asset type: {:?}", name));
+			}
+			if desc.min > desc.max {
+				errors.push(format!("min > max, asset type: {:?}", name));
+			}
+		}
+		if errors.len() > 0 {
+			return Err(AssetConfigError::Invalid(errors));
+		}
+		Ok(())
+	}
+}
+
+/// 资产配置文件
+/// ```json
+/// { "total_capacity": 67108864, "assets": { "TEXTURE_RES": { "ref_garbage": false, "min": 1048576, "max": 10485760, "timeout": 10000 } } }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetConfigFile {
+	#[serde(default)]
+	pub total_capacity: Option<usize>,
+	#[serde(default)]
+	pub assets: XHashMap<String, AssetDesc>,
+}
+
+impl AssetConfigFile {
+	pub fn from_json(s: &str) -> Result<Self, AssetConfigError> {
+		serde_json::from_str(s).map_err(|e| AssetConfigError::Parse(e.to_string()))
+	}
+
+	pub fn from_toml(s: &str) -> Result<Self, AssetConfigError> {
+		toml::from_str(s).map_err(|e| AssetConfigError::Parse(e.to_string()))
+	}
+
+	/// 加载配置文件，按扩展名区分格式（.toml为toml，其他为json）
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AssetConfigError> {
+		let path = path.as_ref();
+		let s = std::fs::read_to_string(path).map_err(|e| AssetConfigError::Io(format!("{:?}, {:?}", path, e)))?;
+		match path.extension().and_then(|r| r.to_str()) {
+			Some("toml") => Self::from_toml(&s),
+			_ => Self::from_json(&s),
+		}
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AssetConfigError {
+	#[error("read asset config fail: {0}")]
+	Io(String),
+	#[error("parse asset config fail: {0}")]
+	Parse(String),
+	#[error("invalid asset config: {0:?}")]
+	Invalid(Vec<String>),
+}
+
+// 注册本库已知的资产类型名
+fn register_asset_names(config: &mut AssetConfig) {
+	config.register::<ImageTexture>();
+	config.register::<ImageTextureView>();
+	config.register::<SamplerRes>();
+	config.register::<TextureRes>();
+	config.register::<EVertexBufferRange>();
+	config.register::<BindGroup>();
+	config.register::<BindGroupLayout>();
+	config.register::<RenderRes<RenderPipeline>>();
 }
 
 /// 资产容量和超时描述
@@ -93,33 +716,216 @@ pub struct AssetDesc {
 }
 
 /// 资源、资产管理器
-#[derive(Resource, Deref, DerefMut)]
-pub struct ShareAssetMgr<A: Asset, G: Garbageer<A> = GarbageEmpty>(pub Share<AssetMgr<A, G>>);
+/// 通过ShareAssetMgr::get取资产时，会统计命中、未命中次数（直接通过AssetMgr取资产不会被统计）
+/// 通过ShareAssetMgr::replace替换的资产（热更），旧资产仍被引用时，只有通过ShareAssetMgr::get才能取到新资产
+#[derive(Resource)]
+pub struct ShareAssetMgr<A: Asset, G: Garbageer<A> = GarbageEmpty>(pub Share<AssetMgr<A, G>>, Share<AssetCounter>, Option<Share<Evictor<A, G>>>, Share<Replaced<A>>);
 
 impl<A: Asset, G: Garbageer<A>> ShareAssetMgr<A, G> {
 	pub fn new_with_config(garbage: G, default: &AssetDesc, asset_config: &AssetConfig, allocator: &mut Allocator) -> Self {
 		let desc = asset_config.get::<A>().unwrap_or(default);
-		let r = AssetMgr::new(garbage, desc.ref_garbage, desc.min, desc.timeout);
-		allocator.register(r.clone(), desc.min, desc.max);
-		Self(r)
+		let r = Self::from(AssetMgr::new(garbage, desc.ref_garbage, desc.min, desc.timeout));
+		allocator.register_entry(asset_config.name::<A>(), r.0.clone(), desc.min, desc.max, Some(desc.timeout), Some(r.1.clone()), Some(r.count_fn()), None);
+		r
+	}
+
+	/// 创建使用指定淘汰策略的资产管理器
+	pub fn new_with_policy(
+		garbage: G,
+		default: &AssetDesc,
+		asset_config: &AssetConfig,
+		allocator: &mut Allocator,
+		policy: impl EvictPolicy,
+	) -> Self {
+		let desc = asset_config.get::<A>().unwrap_or(default);
+		let mut r = Self::from(AssetMgr::new(garbage, desc.ref_garbage, desc.min, desc.timeout));
+		let evictor = Share::new(Evictor::new(r.0.clone(), Box::new(policy)));
+		r.2 = Some(evictor.clone());
+		allocator.register_entry(asset_config.name::<A>(), r.0.clone(), desc.min, desc.max, Some(desc.timeout), Some(r.1.clone()), Some(r.count_fn()), Some(evictor));
+		r
 	}
 
     /// 用指定的参数创建资产管理器， ref_garbage为是否采用引用整理
     pub fn new(garbage: G, ref_garbage: bool, capacity: usize, timeout: usize) -> Self {
-		Self(AssetMgr::new(garbage, ref_garbage, capacity, timeout))
+		Self::from(AssetMgr::new(garbage, ref_garbage, capacity, timeout))
 	}
 
     pub fn create(garbage: G, ref_garbage: bool, cfg: &AssetCapacity) -> Self {
-        Self(AssetMgr::new(garbage, ref_garbage, cfg.min, cfg.timeout))
+        Self::from(AssetMgr::new(garbage, ref_garbage, cfg.min, cfg.timeout))
     }
+
+	/// 取到资产，并统计命中、未命中次数
+	pub fn get(&self, k: &A::Key) -> Option<Handle<A>> {
+		let r = self.3.get(&self.0, k).or_else(|| self.0.get(k));
+		self.1.record(r.is_some());
+		if let (Some(evictor), true) = (&self.2, r.is_some()) {
+			evictor.touch(k, now_millisecond());
+		}
+		#[cfg(feature="leak_detect")]
+		if let Some(r) = &r {
+			leak::record(k, r);
+		}
+		r
+	}
+
+	/// 放入资产，并记录使用时间
+	pub fn insert(&self, k: A::Key, v: A) -> Option<Handle<A>> {
+		if let Some(evictor) = &self.2 {
+			evictor.touch(&k, now_millisecond());
+		}
+		#[cfg(feature="leak_detect")]
+		let key = k.clone();
+		let r = self.0.insert(k, v);
+		#[cfg(feature="leak_detect")]
+		if let Some(r) = &r {
+			leak::record(&key, r);
+		}
+		r
+	}
+
+	/// 替换资产（热更）
+	/// 旧资产未被引用时，直接替换资产管理器中的资产；
+	/// 仍被引用时，旧资产的Handle继续有效，之后通过get取到新资产，旧资产的Handle全部释放后，由资产管理器整理掉
+	pub fn replace(&self, k: A::Key, v: A) -> Option<Handle<A>> {
+		self.3.remove(&k);
+		if self.0.remove(&k).is_some() || !self.0.contains_key(&k) {
+			return self.insert(k, v);
+		}
+		if let Some(evictor) = &self.2 {
+			evictor.touch(&k, now_millisecond());
+		}
+		self.3.insert(k, v)
+	}
+
+	/// 资产是否在资产管理器中（包括替换后的资产）
+	pub fn contains_key(&self, k: &A::Key) -> bool {
+		self.3.get(&self.0, k).is_some() || self.0.contains_key(k)
+	}
+
+	/// 淘汰记录，未指定淘汰策略时为None
+	pub fn evictor(&self) -> Option<&Evictor<A, G>> {
+		self.2.as_deref()
+	}
+
+	/// 命中统计
+	pub fn counter(&self) -> &AssetCounter {
+		&self.1
+	}
+
+	// 统计资产数量
+	fn count_fn(&self) -> Box<dyn Fn() -> usize + Send + Sync> {
+		let mgr = self.0.clone();
+		Box::new(move || mgr.len())
+	}
+}
+
+impl<A: Asset, G: Garbageer<A>> From<Share<AssetMgr<A, G>>> for ShareAssetMgr<A, G> {
+	fn from(value: Share<AssetMgr<A, G>>) -> Self {
+		Self(value, Share::new(AssetCounter::default()), None, Share::new(Replaced::default()))
+	}
+}
+
+impl<A: Asset, G: Garbageer<A>> std::ops::Deref for ShareAssetMgr<A, G> {
+	type Target = Share<AssetMgr<A, G>>;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl<A: Asset, G: Garbageer<A>> std::ops::DerefMut for ShareAssetMgr<A, G> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
 }
 
 impl<A: Asset, G: Garbageer<A>> Clone for ShareAssetMgr<A, G> {
     fn clone(&self) -> Self {
-        Self(self.0.clone())
+        Self(self.0.clone(), self.1.clone(), self.2.clone(), self.3.clone())
     }
 }
 
+// 热更时被替换、但旧资产仍被引用的资产
+// 同一资产管理器中一个键只能有一个资产，新资产放在另外的资产管理器中，多次替换时依次使用
+struct Replaced<A: Asset> {
+	mgrs: ShareMutex<Vec<Share<AssetMgr<A, GarbageEmpty>>>>,
+	// 键 -> (新资产所在的资产管理器, 新资产)
+	map: ShareMutex<XHashMap<A::Key, (Share<AssetMgr<A, GarbageEmpty>>, Handle<A>)>>,
+}
+
+impl<A: Asset> Default for Replaced<A> {
+	fn default() -> Self {
+		Self { mgrs: ShareMutex::new(Vec::new()), map: ShareMutex::new(XHashMap::default()) }
+	}
+}
+
+impl<A: Asset> Replaced<A> {
+	// 取替换后的资产
+	// 旧资产已从资产管理器中移除、且新资产不再被引用时，删除替换记录，返回None（之后可以重新加载到资产管理器中）
+	fn get<G: Garbageer<A>>(&self, mgr: &AssetMgr<A, G>, k: &A::Key) -> Option<Handle<A>> {
+		let mut map = self.map.lock();
+		let (_, r) = map.get(k)?;
+		if Share::strong_count(r) > 1 || (mgr.remove(k).is_none() && mgr.contains_key(k)) {
+			return Some(r.clone());
+		}
+		if let Some((m, r)) = map.remove(k) {
+			drop(r);
+			m.remove(k);
+		}
+		None
+	}
+
+	fn insert(&self, k: A::Key, v: A) -> Option<Handle<A>> {
+		let mgr = {
+			let mut mgrs = self.mgrs.lock();
+			match mgrs.iter().find(|r| r.remove(&k).is_some() || !r.contains_key(&k)) {
+				Some(r) => r.clone(),
+				None => {
+					let r = AssetMgr::new(GarbageEmpty(), false, 0, 0);
+					mgrs.push(r.clone());
+					r
+				}
+			}
+		};
+		let r = mgr.insert(k.clone(), v)?;
+		self.map.lock().insert(k, (mgr, r.clone()));
+		Some(r)
+	}
+
+	// 删除替换记录，新资产不再被引用时从所在的资产管理器中移除
+	fn remove(&self, k: &A::Key) {
+		if let Some((mgr, r)) = self.map.lock().remove(k) {
+			drop(r);
+			mgr.remove(k);
+		}
+	}
+}
+
+/// 资产管理器的命中统计
+#[derive(Debug, Default)]
+pub struct AssetCounter {
+	hits: AtomicUsize,
+	misses: AtomicUsize,
+}
+
+impl AssetCounter {
+	#[inline]
+	pub fn record(&self, hit: bool) {
+		if hit {
+			self.hits.fetch_add(1, Ordering::Relaxed);
+		} else {
+			self.misses.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	pub fn hits(&self) -> usize {
+		self.hits.load(Ordering::Relaxed)
+	}
+
+	pub fn misses(&self) -> usize {
+		self.misses.load(Ordering::Relaxed)
+	}
+}
+
 /// 资源， 同质资产管理器
 #[derive(Resource, Deref, DerefMut)]
 pub struct ShareHomogeneousMgr<A: Size, G: pi_assets::homogeneous::Garbageer<A> = pi_assets::homogeneous::GarbageEmpty>(pub Share<HomogeneousMgr<A, G>>);
@@ -133,7 +939,7 @@ impl<A: Asset + Size, G: pi_assets::homogeneous::Garbageer<A>> ShareHomogeneousM
 	pub fn new_with_config(garbage: G, default: &AssetDesc, asset_config: &AssetConfig, allocator: &mut Allocator) -> Self {
 		let desc = asset_config.get::<A>().unwrap_or(default);
 		let r = HomogeneousMgr::new(garbage, desc.min, desc.timeout);
-		allocator.register(r.clone(), desc.min, desc.max);
+		allocator.register_entry(asset_config.name::<A>(), r.clone(), desc.min, desc.max, Some(desc.timeout), None, None, None);
 		Self(r)
 	}
 }
@@ -145,11 +951,27 @@ impl<A: Size, G: pi_assets::homogeneous::Garbageer<A>> Clone for ShareHomogeneou
 }
 
 
+/// 资产类型的类型名和默认容量配置
+/// 自定义的资产类型用#[derive(AssetCapacity)]实现，为其他库的类型实现时用impl_asset_capacity!
 pub trait TAssetCapacity {
 	const ASSET_TYPE: &'static str;
 	fn capacity() -> AssetCapacity;
 }
 
+/// 为其他库的类型实现TAssetCapacity（如RenderRes<T>，无法在定义处派生）
+/// impl_asset_capacity!(类型, 类型名, min, max, timeout)
+#[macro_export]
+macro_rules! impl_asset_capacity {
+	($ty:ty, $name:expr, $min:expr, $max:expr, $timeout:expr) => {
+		impl $crate::TAssetCapacity for $ty {
+			const ASSET_TYPE: &'static str = $name;
+			fn capacity() -> $crate::AssetCapacity {
+				$crate::AssetCapacity { flag: false, min: $min, max: $max, timeout: $timeout }
+			}
+		}
+	};
+}
+
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct AssetCapacity {
@@ -163,23 +985,16 @@ impl Default for AssetCapacity {
         Self { flag: false, min: 1024, max: 10 * 1024, timeout: 10 * 1000 }
     }
 }
-#[derive(Debug, Default, Clone, Resource, Serialize, Deserialize)]
-pub struct AssetMgrConfigs (pub XHashMap<String, AssetCapacity>);
-impl AssetMgrConfigs {
-	#[inline]
-    pub fn insert(&mut self, key: String, cfg: AssetCapacity) {
-        self.0.insert(key, cfg);
-    }
-    pub fn query<T: TAssetCapacity>(&mut self) -> AssetCapacity  {
-		if let Some(cfg) = self.0.get(T::ASSET_TYPE) {
-			cfg.clone()
-		} else {
-			self.0.insert(String::from(T::ASSET_TYPE), T::capacity());
-			T::capacity()
-		}
-    }
+impl From<AssetDesc> for AssetCapacity {
+	fn from(value: AssetDesc) -> Self {
+		Self { flag: value.ref_garbage, min: value.min, max: value.max, timeout: value.timeout }
+	}
+}
+impl From<AssetCapacity> for AssetDesc {
+	fn from(value: AssetCapacity) -> Self {
+		Self { ref_garbage: value.flag, min: value.min, max: value.max, timeout: value.timeout }
+	}
 }
-
 impl TAssetCapacity for ImageTexture {
 	const ASSET_TYPE: &'static str = "IMAGE_TEXTURE";
 	fn capacity() -> AssetCapacity {
@@ -229,6 +1044,13 @@ impl TAssetCapacity for RenderRes<RenderPipeline> {
 	}
 }
 
+// 渲染插件创建的资产管理器
+impl_asset_capacity!(RenderRes<TextureView>, "RENDER_RES_TEXTURE_VIEW", 30 * 1024 * 1024, 600 * 1024 * 1024, 10 * 60 * 1000);
+impl_asset_capacity!(RenderRes<UnuseTexture>, "RENDER_RES_UNUSE_TEXTURE", 10 * std::mem::size_of::<UnuseTexture>(), 20 * std::mem::size_of::<UnuseTexture>(), 10 * 60 * 1000);
+impl_asset_capacity!(RenderRes<Buffer>, "RENDER_RES_BUFFER", 10 * 1024 * 1024, 50 * 1024 * 1024, 10 * 60 * 1000);
+impl_asset_capacity!(RenderRes<RenderBindGroup>, "RENDER_RES_BIND_GROUP", 5 * 1024 * 1024, 10 * 1024 * 1024, 10 * 60 * 1000);
+impl_asset_capacity!(AssetWithId<TextureRes>, "ASSET_WITH_ID_TEXTURE_RES", 10 * 1024 * 1024, 600 * 1024 * 1024, 10 * 60 * 1000);
+
 #[cfg(feature="account_info")]
 fn account(allotor: ResMut<Allocator>, mut pre_time: Local<u64>) {
 	if *pre_time == 0 {
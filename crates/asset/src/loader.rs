@@ -0,0 +1,590 @@
+//! 异步资产加载
+//! 按扩展名注册AssetLoader，通过AssetServer::load::<A>(path)加载资产：
+//! 资产已在资产管理器中时立即返回，否则在PiAsyncRuntime上读取、解码，完成后放入资产管理器；
+//! 同一资产的并发请求只会加载一次；加载完成、失败时分别发出AssetLoaded<A>、AssetLoadFailed事件
+//! 添加AssetHotReloadPlugin后，会定时检查已加载资产的源文件（在阻塞操作线程池上读取修改时间），文件修改后重新加载，
+//! 通过ShareAssetMgr::replace替换资产管理器中的资产（旧资产仍被引用时，旧的Handle继续有效，之后取到新资产），发出AssetReloaded事件；
+//! 资产被整理出资产管理器后，不再监视其源文件
+
+use std::any::{Any, TypeId};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use bevy_app::{App, First, Plugin};
+use bevy_ecs::{
+	event::{Event, Events},
+	prelude::World,
+	schedule::IntoSystemConfigs,
+	system::{Res, ResMut, Resource, SystemParam},
+};
+use pi_time::now_millisecond;
+use pi_assets::asset::{Asset, GarbageEmpty, Handle};
+use pi_bevy_ecs_extend::async_system::{AsyncSyncPoint, AsyncTaskRegistry, AsyncTasks, AsyncTasksPlugin};
+use pi_futures::BoxFuture;
+use pi_hash::XHashMap;
+use pi_share::{Share, ShareMutex};
+
+use crate::{Allocator, AssetConfig, AssetDeps, AssetDesc, AssetId, AssetInvalidated, ShareAssetMgr, invalidate_reloaded};
+
+/// 资产加载器
+pub trait AssetLoader: Send + Sync + 'static {
+	type Asset: Asset;
+
+	/// 支持的扩展名（不含"."）
+	fn extensions(&self) -> &[&'static str];
+
+	/// 资产在资产管理器中的键
+	fn key(&self, path: &str) -> <Self::Asset as Asset>::Key;
+
+	/// 读取资产数据，默认在阻塞操作线程池上从文件系统读取（不阻塞异步运行时）
+	fn read(&self, path: &str) -> BoxFuture<'static, Result<Vec<u8>, AssetLoadError>> {
+		let path = path.to_string();
+		Box::pin(async move {
+			match spawn_blocking(move || std::fs::read(&path)).await {
+				Some(r) => r.map_err(|e| AssetLoadError::Io(format!("{:?}", e))),
+				None => Err(AssetLoadError::Cancelled),
+			}
+		})
+	}
+
+	/// 解码资产
+	fn decode(&self, path: &str, data: Vec<u8>) -> BoxFuture<'static, Result<Self::Asset, AssetLoadError>>;
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum AssetLoadError {
+	#[error("no loader, path: {0}")]
+	NoLoader(String),
+	#[error("read asset fail: {0}")]
+	Io(String),
+	#[error("decode asset fail: {0}")]
+	Decode(String),
+	#[error("insert asset fail, path: {0}")]
+	Insert(String),
+	#[error("cancelled")]
+	Cancelled,
+}
+
+/// 加载状态
+pub enum LoadState<A: Asset> {
+	Loading,
+	Loaded(Handle<A>),
+	Failed(AssetLoadError),
+}
+
+impl<A: Asset> Clone for LoadState<A> {
+	fn clone(&self) -> Self {
+		match self {
+			Self::Loading => Self::Loading,
+			Self::Loaded(r) => Self::Loaded(r.clone()),
+			Self::Failed(r) => Self::Failed(r.clone()),
+		}
+	}
+}
+
+/// 加载句柄，资产加载完成后可以从句柄上取到资产
+pub struct LoadHandle<A: Asset> {
+	path: Share<str>,
+	state: Share<ShareMutex<LoadState<A>>>,
+}
+
+impl<A: Asset> Clone for LoadHandle<A> {
+	fn clone(&self) -> Self {
+		Self { path: self.path.clone(), state: self.state.clone() }
+	}
+}
+
+impl<A: Asset> LoadHandle<A> {
+	fn new(path: &str, state: LoadState<A>) -> Self {
+		Self { path: path.into(), state: Share::new(ShareMutex::new(state)) }
+	}
+
+	pub fn path(&self) -> &str {
+		&self.path
+	}
+
+	pub fn state(&self) -> LoadState<A> {
+		self.state.lock().clone()
+	}
+
+	/// 加载完成的资产
+	pub fn get(&self) -> Option<Handle<A>> {
+		match &*self.state.lock() {
+			LoadState::Loaded(r) => Some(r.clone()),
+			_ => None,
+		}
+	}
+
+	pub fn is_loading(&self) -> bool {
+		matches!(&*self.state.lock(), LoadState::Loading)
+	}
+
+	pub fn is_failed(&self) -> bool {
+		matches!(&*self.state.lock(), LoadState::Failed(_))
+	}
+
+	fn set(&self, state: LoadState<A>) {
+		*self.state.lock() = state;
+	}
+}
+
+// 加载结束（完成、失败，或任务被丢弃）时，从加载中的资产中删除；任务被丢弃时，句柄的状态设为Cancelled
+struct LoadingGuard<A: Asset> {
+	loading: Share<ShareMutex<XHashMap<(TypeId, String), Box<dyn Any + Send + Sync>>>>,
+	key: (TypeId, String),
+	handle: LoadHandle<A>,
+}
+
+impl<A: Asset> Drop for LoadingGuard<A> {
+	fn drop(&mut self) {
+		self.loading.lock().remove(&self.key);
+		let mut state = self.handle.state.lock();
+		if let LoadState::Loading = &*state {
+			*state = LoadState::Failed(AssetLoadError::Cancelled);
+		}
+	}
+}
+
+// 阻塞操作线程池的线程数，超出的操作排队等待
+const BLOCKING_THREADS: usize = 4;
+
+type BlockingJob = Box<dyn FnOnce() + Send>;
+
+// 阻塞操作线程池，第一次使用时创建；线程全部创建失败时为None
+fn blocking_pool() -> Option<&'static flume::Sender<BlockingJob>> {
+	static POOL: OnceLock<Option<flume::Sender<BlockingJob>>> = OnceLock::new();
+	POOL.get_or_init(|| {
+		let (sender, receiver) = flume::unbounded::<BlockingJob>();
+		let mut count = 0;
+		for i in 0..BLOCKING_THREADS {
+			let receiver = receiver.clone();
+			let spawned = std::thread::Builder::new().name(format!("pi_bevy_asset_io_{}", i)).spawn(move || {
+				while let Ok(job) = receiver.recv() {
+					// 操作panic时，不影响线程继续处理后续操作
+					let _ = catch_unwind(AssertUnwindSafe(job));
+				}
+			});
+			match spawned {
+				Ok(_) => count += 1,
+				Err(e) => log::warn!("spawn blocking thread fail, err: {:?}", e),
+			}
+		}
+		if count > 0 {
+			Some(sender)
+		} else {
+			None
+		}
+	})
+	.as_ref()
+}
+
+/// 在阻塞操作线程池上执行阻塞操作（如文件读写），不阻塞异步运行时；
+/// 线程池固定BLOCKING_THREADS个线程，同时提交的操作多于线程数时排队；线程池创建失败、或操作panic时返回None
+pub fn spawn_blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> BoxFuture<'static, Option<T>> {
+	let (sender, receiver) = flume::bounded(1);
+	let sent = match blocking_pool() {
+		Some(pool) => pool
+			.send(Box::new(move || {
+				let _ = sender.send(f());
+			}))
+			.is_ok(),
+		None => false,
+	};
+	Box::pin(async move {
+		if !sent {
+			return None;
+		}
+		receiver.recv_async().await.ok()
+	})
+}
+
+/// 资产加载完成
+pub struct AssetLoaded<A: Asset> {
+	pub path: String,
+	pub handle: Handle<A>,
+}
+
+impl<A: Asset> Event for AssetLoaded<A> {}
+
+/// 资产被重新加载（资产管理器中的资产已被替换）
+#[derive(Debug, Clone, Event)]
+pub struct AssetReloaded {
+	pub path: String,
+	/// 资产类型
+	pub ty: &'static str,
+	pub type_id: TypeId,
+	pub id: AssetId,
+}
+
+/// 资产加载失败
+#[derive(Debug, Clone, Event)]
+pub struct AssetLoadFailed {
+	pub path: String,
+	/// 资产类型
+	pub ty: &'static str,
+	pub error: AssetLoadError,
+}
+
+/// 类型擦除的加载句柄
+pub trait DynLoadHandle: Send + Sync {
+	fn path(&self) -> &str;
+	fn is_loading(&self) -> bool;
+	fn is_failed(&self) -> bool;
+}
+
+impl<A: Asset> DynLoadHandle for LoadHandle<A> {
+	fn path(&self) -> &str {
+		LoadHandle::path(self)
+	}
+
+	fn is_loading(&self) -> bool {
+		LoadHandle::is_loading(self)
+	}
+
+	fn is_failed(&self) -> bool {
+		LoadHandle::is_failed(self)
+	}
+}
+
+// 没有加载器的资产
+struct NoLoaderHandle(String);
+
+impl DynLoadHandle for NoLoaderHandle {
+	fn path(&self) -> &str {
+		&self.0
+	}
+
+	fn is_loading(&self) -> bool {
+		false
+	}
+
+	fn is_failed(&self) -> bool {
+		true
+	}
+}
+
+// 按扩展名加载，资产类型由注册的加载器决定
+type LoadDynFn = for<'a, 'w> fn(&'a AssetServer<'w>, &str) -> Box<dyn DynLoadHandle>;
+
+struct LoaderEntry<A: Asset> {
+	loader: Share<dyn AssetLoader<Asset = A>>,
+	mgr: ShareAssetMgr<A>,
+}
+
+/// 已注册的加载器和加载中的资产
+#[derive(Resource, Default)]
+pub struct AssetLoaders {
+	// (资产类型, 扩展名) -> LoaderEntry<A>
+	loaders: XHashMap<(TypeId, String), Box<dyn Any + Send + Sync>>,
+	// 扩展名 -> 加载函数（同一扩展名注册了多个资产类型时，取最后注册的）
+	by_ext: XHashMap<String, LoadDynFn>,
+	// (资产类型, 路径) -> LoadHandle<A>
+	loading: Share<ShareMutex<XHashMap<(TypeId, String), Box<dyn Any + Send + Sync>>>>,
+	// 是否监视已加载资产的源文件
+	watch: bool,
+	// (资产类型, 路径) -> 监视的资产
+	watched: Share<ShareMutex<XHashMap<(TypeId, String), WatchEntry>>>,
+}
+
+// 被监视的资产
+struct WatchEntry {
+	modified: Option<SystemTime>,
+	reload: Share<dyn Fn(&AsyncTasks) + Send + Sync>,
+	// 资产是否还在资产管理器中
+	present: Box<dyn Fn() -> bool + Send + Sync>,
+}
+
+impl AssetLoaders {
+	/// 加载中的资产数量
+	pub fn loading_count(&self) -> usize {
+		self.loading.lock().len()
+	}
+}
+
+/// 加载资产
+#[derive(SystemParam)]
+pub struct AssetServer<'w> {
+	tasks: AsyncTasks<'w>,
+	loaders: Res<'w, AssetLoaders>,
+}
+
+impl<'w> AssetServer<'w> {
+	/// 按扩展名加载资产，资产类型由该扩展名注册的加载器决定
+	pub fn load_dyn(&self, path: &str) -> Box<dyn DynLoadHandle> {
+		let ext = Path::new(path).extension().and_then(|r| r.to_str()).unwrap_or("");
+		match self.loaders.by_ext.get(ext) {
+			Some(load) => load(self, path),
+			None => {
+				let error = AssetLoadError::NoLoader(path.to_string());
+				let path = path.to_string();
+				let r = Box::new(NoLoaderHandle(path.clone()));
+				self.tasks.spawn(async move { Box::new(move |world: &mut World| send_failed::<()>(world, path, error)) as Box<dyn FnOnce(&mut World) + Send> });
+				r
+			}
+		}
+	}
+
+	pub fn load<A: Asset>(&self, path: &str) -> LoadHandle<A> {
+		let ty = TypeId::of::<A>();
+		let ext = Path::new(path).extension().and_then(|r| r.to_str()).unwrap_or("");
+		let entry = match self.loaders.loaders.get(&(ty, ext.to_string())).and_then(|r| r.downcast_ref::<LoaderEntry<A>>()) {
+			Some(r) => r,
+			None => {
+				let error = AssetLoadError::NoLoader(path.to_string());
+				let path = path.to_string();
+				let r = LoadHandle::new(&path, LoadState::Failed(error.clone()));
+				self.tasks.spawn(async move { Box::new(move |world: &mut World| send_failed::<A>(world, path, error)) as Box<dyn FnOnce(&mut World) + Send> });
+				return r;
+			}
+		};
+
+		// 已在资产管理器中
+		let key = entry.loader.key(path);
+		if let Some(r) = entry.mgr.get(&key) {
+			return LoadHandle::new(path, LoadState::Loaded(r));
+		}
+
+		// 正在加载中
+		let loading_key = (ty, path.to_string());
+		let mut loading = self.loaders.loading.lock();
+		if let Some(r) = loading.get(&loading_key).and_then(|r| r.downcast_ref::<LoadHandle<A>>()) {
+			return r.clone();
+		}
+		let handle = LoadHandle::new(path, LoadState::Loading);
+		loading.insert(loading_key.clone(), Box::new(handle.clone()));
+		drop(loading);
+
+		let (loader, mgr) = (entry.loader.clone(), entry.mgr.clone());
+		let guard = LoadingGuard { loading: self.loaders.loading.clone(), key: loading_key.clone(), handle: handle.clone() };
+		let watched = if self.loaders.watch { Some(self.loaders.watched.clone()) } else { None };
+		let path = path.to_string();
+		self.tasks.spawn(async move {
+			let result = match loader.read(&path).await {
+				Ok(data) => loader.decode(&path, data).await,
+				Err(e) => Err(e),
+			};
+			let result = result.and_then(|asset| match mgr.insert(key.clone(), asset) {
+				Some(r) => Ok(r),
+				// 加载期间已被其他途径放入资产管理器（取不到时，已被整理或删除）
+				None => mgr.get(&key).ok_or_else(|| AssetLoadError::Insert(path.clone())),
+			});
+			guard.handle.set(match &result {
+				Ok(r) => LoadState::Loaded(r.clone()),
+				Err(e) => LoadState::Failed(e.clone()),
+			});
+			drop(guard);
+			if let (Some(watched), Ok(_)) = (watched, &result) {
+				let p = path.clone();
+				let modified = spawn_blocking(move || std::fs::metadata(&p).and_then(|r| r.modified()).ok()).await.flatten();
+				let present = {
+					let (mgr, key) = (mgr.clone(), key.clone());
+					Box::new(move || mgr.contains_key(&key))
+				};
+				let reload = reload_fn(loader, mgr, key, path.clone());
+				watched.lock().insert(loading_key, WatchEntry { modified, reload, present });
+			}
+
+			Box::new(move |world: &mut World| match result {
+				Ok(handle) => {
+					if let Some(mut events) = world.get_resource_mut::<Events<AssetLoaded<A>>>() {
+						events.send(AssetLoaded { path, handle });
+					}
+				}
+				Err(error) => send_failed::<A>(world, path, error),
+			}) as Box<dyn FnOnce(&mut World) + Send>
+		});
+		handle
+	}
+}
+
+// 重新加载资产，在同步点替换资产管理器中的资产
+fn reload_fn<A: Asset>(
+	loader: Share<dyn AssetLoader<Asset = A>>,
+	mgr: ShareAssetMgr<A>,
+	key: A::Key,
+	path: String,
+) -> Share<dyn Fn(&AsyncTasks) + Send + Sync> {
+	Share::new(move |tasks: &AsyncTasks| {
+		let (loader, mgr, key, path) = (loader.clone(), mgr.clone(), key.clone(), path.clone());
+		tasks.spawn(async move {
+			let result = match loader.read(&path).await {
+				Ok(data) => loader.decode(&path, data).await,
+				Err(e) => Err(e),
+			};
+			Box::new(move |world: &mut World| match result {
+				// 重新加载期间资产已被整理，不再放回资产管理器
+				Ok(_) if !mgr.contains_key(&key) => (),
+				Ok(asset) => {
+					let id = AssetId::of::<A>(&key);
+					// 旧资产仍被引用时，旧的Handle继续有效，之后取到的是新资产
+					if mgr.replace(key, asset).is_none() {
+						return send_failed::<A>(world, path.clone(), AssetLoadError::Insert(path));
+					}
+					if let Some(mut events) = world.get_resource_mut::<Events<AssetReloaded>>() {
+						events.send(AssetReloaded { path, ty: std::any::type_name::<A>(), type_id: TypeId::of::<A>(), id });
+					}
+				}
+				Err(error) => send_failed::<A>(world, path, error),
+			}) as Box<dyn FnOnce(&mut World) + Send>
+		});
+	})
+}
+
+/// 资产热更插件，定时检查已加载资产的源文件（只对文件系统中的资产有效）
+pub struct AssetHotReloadPlugin {
+	/// 检查间隔（毫秒）
+	pub interval: u64,
+}
+
+impl Default for AssetHotReloadPlugin {
+	fn default() -> Self {
+		Self { interval: 500 }
+	}
+}
+
+impl Plugin for AssetHotReloadPlugin {
+	fn build(&self, app: &mut App) {
+		if !app.world.contains_resource::<AssetLoaders>() {
+			app.init_resource::<AssetLoaders>().add_event::<AssetLoadFailed>();
+		}
+		if !app.world.contains_resource::<AssetDeps>() {
+			app.init_resource::<AssetDeps>().add_event::<AssetInvalidated>();
+		}
+		app.world.resource_mut::<AssetLoaders>().watch = true;
+		app.insert_resource(AssetWatcher { interval: self.interval, last_time: 0, checking: Share::new(AtomicBool::new(false)), changed: Share::new(ShareMutex::new(Vec::new())) })
+			.add_event::<AssetReloaded>()
+			.add_systems(First, (check_asset_changes, invalidate_reloaded.after(AsyncSyncPoint)));
+	}
+}
+
+/// 资产源文件检查
+#[derive(Debug, Resource)]
+pub struct AssetWatcher {
+	pub interval: u64,
+	last_time: u64,
+	// 是否正在读取源文件的修改时间
+	checking: Share<AtomicBool>,
+	// 上次检查发现修改过的资产
+	changed: Share<ShareMutex<Vec<(TypeId, String)>>>,
+}
+
+// 检查结束（或任务被丢弃）时，重置检查状态
+struct CheckingGuard(Share<AtomicBool>);
+
+impl Drop for CheckingGuard {
+	fn drop(&mut self) {
+		self.0.store(false, Ordering::Release);
+	}
+}
+
+/// 检查已加载资产的源文件，修改过的重新加载
+/// 源文件的修改时间在独立线程上读取，发现修改后，下次运行时重新加载
+pub fn check_asset_changes(server: AssetServer, mut watcher: ResMut<AssetWatcher>) {
+	let changed = std::mem::take(&mut *watcher.changed.lock());
+	if changed.len() > 0 {
+		let watched = server.loaders.watched.lock();
+		for key in changed.iter() {
+			if let Some(entry) = watched.get(key) {
+				(entry.reload)(&server.tasks);
+			}
+		}
+	}
+
+	let now = now_millisecond();
+	if now.saturating_sub(watcher.last_time) < watcher.interval || watcher.checking.load(Ordering::Acquire) {
+		return;
+	}
+	watcher.last_time = now;
+
+	let paths: Vec<(TypeId, String)> = {
+		let mut watched = server.loaders.watched.lock();
+		// 已被整理出资产管理器的资产，不再监视
+		watched.retain(|_, entry| (entry.present)());
+		watched.keys().cloned().collect()
+	};
+	if paths.is_empty() {
+		return;
+	}
+
+	watcher.checking.store(true, Ordering::Release);
+	let guard = CheckingGuard(watcher.checking.clone());
+	let (watched, changed) = (server.loaders.watched.clone(), watcher.changed.clone());
+	server.tasks.spawn(async move {
+		let modified = spawn_blocking(move || {
+			paths.into_iter().map(|key| {
+				let modified = std::fs::metadata(&key.1).and_then(|r| r.modified()).ok();
+				(key, modified)
+			}).collect::<Vec<_>>()
+		}).await;
+		let mut watched = watched.lock();
+		for (key, modified) in modified.into_iter().flatten() {
+			if let Some(entry) = watched.get_mut(&key) {
+				if modified.is_some() && modified != entry.modified {
+					entry.modified = modified;
+					changed.lock().push(key);
+				}
+			}
+		}
+		drop(guard);
+	});
+}
+
+fn load_dyn<A: Asset>(server: &AssetServer, path: &str) -> Box<dyn DynLoadHandle> {
+	Box::new(server.load::<A>(path))
+}
+
+fn send_failed<A: ?Sized + 'static>(world: &mut World, path: String, error: AssetLoadError) {
+	log::warn!("load asset fail, path: {:?}, err: {:?}", path, error);
+	if let Some(mut events) = world.get_resource_mut::<Events<AssetLoadFailed>>() {
+		events.send(AssetLoadFailed { path, ty: std::any::type_name::<A>(), error });
+	}
+}
+
+pub trait AddAssetLoader {
+	/// 注册资产加载器，资产管理器ShareAssetMgr<A>不存在时，按资产配置创建
+	fn add_asset_loader<L: AssetLoader>(&mut self, loader: L) -> &mut Self;
+}
+
+impl AddAssetLoader for App {
+	fn add_asset_loader<L: AssetLoader>(&mut self, loader: L) -> &mut Self {
+		if !self.world.contains_resource::<AsyncTaskRegistry>() {
+			self.add_plugins(AsyncTasksPlugin);
+		}
+		if !self.world.contains_resource::<AssetLoaders>() {
+			self.init_resource::<AssetLoaders>().add_event::<AssetLoadFailed>();
+		}
+		if !self.world.contains_resource::<Events<AssetLoaded<L::Asset>>>() {
+			self.add_event::<AssetLoaded<L::Asset>>();
+		}
+
+		let mgr = match self.world.get_resource::<ShareAssetMgr<L::Asset>>() {
+			Some(r) => r.clone(),
+			None => {
+				let r = self.world.resource_scope(|world, mut allocator: bevy_ecs::world::Mut<Allocator>| {
+					ShareAssetMgr::<L::Asset, GarbageEmpty>::new_with_config(
+						GarbageEmpty(),
+						&AssetDesc { ref_garbage: false, min: 1024 * 1024, max: 10 * 1024 * 1024, timeout: 10 * 1000 },
+						world.resource::<AssetConfig>(),
+						&mut allocator,
+					)
+				});
+				self.insert_resource(r.clone());
+				r
+			}
+		};
+
+		let loader: Share<dyn AssetLoader<Asset = L::Asset>> = Share::new(loader);
+		let mut loaders = self.world.resource_mut::<AssetLoaders>();
+		for ext in loader.extensions() {
+			loaders.loaders.insert(
+				(TypeId::of::<L::Asset>(), ext.to_string()),
+				Box::new(LoaderEntry { loader: loader.clone(), mgr: mgr.clone() }),
+			);
+			loaders.by_ext.insert(ext.to_string(), load_dyn::<L::Asset>);
+		}
+		self
+	}
+}
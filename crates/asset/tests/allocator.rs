@@ -0,0 +1,70 @@
+//! 运行时修改容量分配
+
+use pi_assets::asset::{Asset, GarbageEmpty, Size};
+use pi_bevy_asset::{Allocator, ShareAssetMgr};
+
+struct Data(usize);
+
+impl Asset for Data {
+	type Key = u32;
+}
+
+impl Size for Data {
+	fn size(&self) -> usize {
+		self.0
+	}
+}
+
+const BASE: usize = 64 * 1024 * 1024;
+
+fn new_allocator() -> Allocator {
+	let mut allocator = Allocator::new(BASE);
+	let mgr = ShareAssetMgr::<Data>::new(GarbageEmpty(), false, 1024, 1000);
+	allocator.register_named(Some("DATA"), mgr.0.clone(), 1024, BASE);
+	allocator.collect(0);
+	allocator
+}
+
+fn capacity(allocator: &Allocator) -> usize {
+	allocator.stats()[0].capacity
+}
+
+#[test]
+fn repeated_total_changes_do_not_stack() {
+	let mut once = new_allocator();
+	once.set_total_capacity(BASE / 4);
+
+	let mut twice = new_allocator();
+	twice.set_total_capacity(BASE / 2);
+	twice.set_total_capacity(BASE / 4);
+	assert_eq!(capacity(&twice), capacity(&once));
+
+	// 恢复总容量后，恢复原来的分配
+	let base = capacity(&new_allocator());
+	twice.set_total_capacity(BASE);
+	assert_eq!(capacity(&twice), base);
+}
+
+#[test]
+fn reconfigure_after_total_change() {
+	let mut once = new_allocator();
+	once.set_total_capacity(BASE / 2);
+	let expected = capacity(&once);
+
+	let mut r = new_allocator();
+	r.set_total_capacity(BASE / 2);
+	assert!(r.reconfigure("DATA", 1024, BASE * 2));
+	assert_eq!(capacity(&r), expected);
+	assert!(r.reconfigure("DATA", 1024, BASE));
+	assert_eq!(capacity(&r), expected);
+}
+
+#[test]
+fn collect_keeps_scaled_capacity() {
+	let mut once = new_allocator();
+	once.set_total_capacity(BASE / 2);
+	let expected = capacity(&once);
+	once.collect(1);
+	once.collect(2);
+	assert_eq!(capacity(&once), expected);
+}
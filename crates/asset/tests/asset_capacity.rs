@@ -0,0 +1,24 @@
+//! #[derive(AssetCapacity)]
+
+use pi_bevy_asset::{AssetCapacity, TAssetCapacity};
+
+#[derive(AssetCapacity)]
+#[asset_capacity(name = "MY_TEXTURE", min = 1024, max = 4 * 1024, timeout = 1000, ref_garbage = true)]
+struct MyTexture;
+
+#[derive(AssetCapacity)]
+struct MeshData;
+
+#[test]
+fn derive_with_attributes() {
+	assert_eq!(MyTexture::ASSET_TYPE, "MY_TEXTURE");
+	let r = MyTexture::capacity();
+	assert_eq!((r.flag, r.min, r.max, r.timeout), (true, 1024, 4 * 1024, 1000));
+}
+
+#[test]
+fn derive_default() {
+	assert_eq!(MeshData::ASSET_TYPE, "MESH_DATA");
+	let (r, d) = (MeshData::capacity(), AssetCapacity::default());
+	assert_eq!((r.flag, r.min, r.max, r.timeout), (d.flag, d.min, d.max, d.timeout));
+}
@@ -0,0 +1,25 @@
+//! 资产配置校验
+
+use bevy_app::App;
+use pi_bevy_asset::{AssetConfig, AssetConfigError, AssetDesc, PiAssetPlugin};
+
+#[test]
+#[should_panic(expected = "unknown asset type")]
+fn unknown_name_panics() {
+	let mut asset_config = AssetConfig::default();
+	asset_config.insert_by_name("TEXTRUE_RES".to_string(), AssetDesc { ref_garbage: false, min: 1024, max: 2048, timeout: 1000 });
+
+	let mut app = App::new();
+	app.add_plugins(PiAssetPlugin { asset_config, ..Default::default() });
+	app.finish();
+}
+
+#[test]
+fn file_min_greater_than_max() {
+	let path = std::env::temp_dir().join("pi_bevy_asset_config_test.json");
+	std::fs::write(&path, r#"{ "assets": { "TEXTURE_RES": { "ref_garbage": false, "min": 2048, "max": 1024, "timeout": 1000 } } }"#).unwrap();
+
+	let r = PiAssetPlugin::from_file(&path);
+	let _ = std::fs::remove_file(&path);
+	assert!(matches!(r, Err(AssetConfigError::Invalid(_))));
+}
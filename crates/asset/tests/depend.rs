@@ -0,0 +1,52 @@
+//! 释放被依赖的资产
+
+use pi_assets::asset::{Asset, GarbageEmpty, Size};
+use pi_bevy_asset::{AssetDeps, AssetId, ShareAssetMgr};
+
+struct Text(&'static str);
+
+impl Asset for Text {
+	type Key = u32;
+}
+
+impl Size for Text {
+	fn size(&self) -> usize {
+		self.0.len()
+	}
+}
+
+#[test]
+fn release_dependents() {
+	let mgr = ShareAssetMgr::<Text>::new(GarbageEmpty(), false, 1024, 1000);
+	drop(mgr.insert(1, Text("shader")).unwrap());
+	drop(mgr.insert(2, Text("pipeline")).unwrap());
+
+	let mut deps = AssetDeps::default();
+	let shader = deps.record(&mgr, &1, []);
+	let pipeline = deps.record(&mgr, &2, [shader.clone()]);
+
+	assert_eq!(deps.release(&shader), vec![pipeline]);
+	assert!(!mgr.contains_key(&1));
+	assert!(!mgr.contains_key(&2));
+	assert_eq!(deps.len(), 0);
+}
+
+#[test]
+fn keep_referenced_dependents() {
+	let mgr = ShareAssetMgr::<Text>::new(GarbageEmpty(), false, 1024, 1000);
+	drop(mgr.insert(1, Text("shader")).unwrap());
+	let held = mgr.insert(2, Text("pipeline")).unwrap();
+
+	let mut deps = AssetDeps::default();
+	let shader = deps.record(&mgr, &1, []);
+	let pipeline = deps.record(&mgr, &2, [shader.clone()]);
+
+	// pipeline仍被引用，无法移除，不报告为失效，依赖记录保留
+	assert!(deps.invalidate(&shader).is_empty());
+	assert!(mgr.contains_key(&2));
+	assert_eq!(deps.dependents(&shader).collect::<Vec<&AssetId>>(), vec![&pipeline]);
+
+	drop(held);
+	assert_eq!(deps.invalidate(&shader), vec![pipeline]);
+	assert!(!mgr.contains_key(&2));
+}
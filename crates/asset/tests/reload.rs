@@ -0,0 +1,44 @@
+//! 热更替换仍被引用的资产
+
+use pi_assets::asset::{Asset, GarbageEmpty, Size};
+use pi_bevy_asset::ShareAssetMgr;
+
+struct Text(&'static str);
+
+impl Asset for Text {
+	type Key = u32;
+}
+
+impl Size for Text {
+	fn size(&self) -> usize {
+		self.0.len()
+	}
+}
+
+#[test]
+fn replace_while_handle_held() {
+	let mgr = ShareAssetMgr::<Text>::new(GarbageEmpty(), false, 1024, 1000);
+	let old = mgr.insert(1, Text("old")).unwrap();
+
+	let new = mgr.replace(1, Text("new")).unwrap();
+	assert_eq!(new.0, "new");
+	// 旧的Handle继续有效，之后取到的是新资产
+	assert_eq!(old.0, "old");
+	assert_eq!(mgr.get(&1).unwrap().0, "new");
+	assert!(mgr.contains_key(&1));
+
+	// 再次替换
+	drop(new);
+	mgr.replace(1, Text("newer")).unwrap();
+	assert_eq!(mgr.get(&1).unwrap().0, "newer");
+	assert_eq!(old.0, "old");
+}
+
+#[test]
+fn replace_unreferenced() {
+	let mgr = ShareAssetMgr::<Text>::new(GarbageEmpty(), false, 1024, 1000);
+	drop(mgr.insert(1, Text("old")).unwrap());
+
+	mgr.replace(1, Text("new")).unwrap();
+	assert_eq!(mgr.get(&1).unwrap().0, "new");
+}
@@ -1,19 +1,329 @@
+//! 操作列表
+//! 操作在帧内通过ActionWriter（或ActionList::push）写入，在帧边界（First阶段）统一应用：
+//! 按操作类型的优先级依次处理（优先级相同时按注册顺序），合并键相同的操作，之后本帧内的system可以通过ActionReader读取
+//! 注册为可回放的操作，可以将每帧的操作记录到ActionLog中，并通过ActionReplay按帧回放，用于问题的确定性重现；
+//! 可回放的操作注册时需要指定稳定的名字，日志中以该名字标识操作类型（类型名随编译器版本和模块路径变化，不能用于持久化的日志）
+
+use std::any::TypeId;
+use std::collections::VecDeque;
 use std::mem::replace;
+use std::slice::Iter;
+
+use bevy_app::{App, First};
+use bevy_ecs::{
+	prelude::{Resource, World},
+	system::{Res, ResMut, SystemParam},
+	world::Mut,
+};
+use pi_hash::XHashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use bevy_ecs::prelude::Resource;
+/// 操作
+pub trait Action: Send + Sync + 'static {
+	/// 应用优先级，帧边界处，数值小的操作类型先应用
+	const PRIORITY: i32 = 0;
+
+	/// 合并键，一帧内键相同的操作只保留最后一个（位于第一个操作的位置），None表示不合并
+	fn key(&self) -> Option<u64> {
+		None
+	}
+}
 
 #[derive(Resource)]
-pub struct ActionList<T: Send + Sync + 'static>(Vec<T>);
+pub struct ActionList<T: Send + Sync + 'static> {
+	// 本帧写入、尚未应用的操作
+	list: Vec<T>,
+	// 上一次帧边界应用的操作
+	frame: Vec<T>,
+}
 impl<T: Send + Sync> Default for ActionList<T> {
     fn default() -> Self {
-        Self(vec![])
+        Self {
+			list: vec![],
+			frame: vec![],
+		}
     }
 }
 impl<T: Send + Sync> ActionList<T> {
     pub fn push(&mut self, val: T) {
-        self.0.push(val);
+        self.list.push(val);
     }
     pub fn drain(&mut self) -> Vec<T> {
-        replace(&mut self.0, vec![])
+        replace(&mut self.list, vec![])
     }
+
+	/// 本帧已应用的操作
+	pub fn frame(&self) -> &[T] {
+		&self.frame
+	}
+
+	/// 取走本帧已应用的操作（之后本帧的ActionReader将读不到这些操作）
+	pub fn take_frame(&mut self) -> Vec<T> {
+		replace(&mut self.frame, vec![])
+	}
+}
+
+/// 写入操作， 操作在下一个帧边界被应用
+#[derive(SystemParam)]
+pub struct ActionWriter<'w, T: Action> {
+	list: ResMut<'w, ActionList<T>>,
+}
+
+impl<'w, T: Action> ActionWriter<'w, T> {
+	#[inline]
+	pub fn send(&mut self, val: T) {
+		self.list.push(val);
+	}
+
+	#[inline]
+	pub fn send_batch(&mut self, vals: impl IntoIterator<Item = T>) {
+		self.list.list.extend(vals);
+	}
+}
+
+/// 读取本帧已应用的操作
+#[derive(SystemParam)]
+pub struct ActionReader<'w, T: Action> {
+	list: Res<'w, ActionList<T>>,
+}
+
+impl<'w, T: Action> ActionReader<'w, T> {
+	#[inline]
+	pub fn iter(&self) -> Iter<T> {
+		self.list.frame.iter()
+	}
+
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.list.frame.len()
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.list.frame.is_empty()
+	}
+}
+
+/// 一个被记录的操作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionRecord {
+	/// 操作类型注册时指定的名字
+	pub ty: String,
+	/// 操作的json序列化结果
+	pub data: String,
+}
+
+/// 一帧中被记录的操作
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActionFrame {
+	pub frame: u64,
+	pub actions: Vec<ActionRecord>,
+}
+
+/// 操作日志，记录中时，每帧应用的可回放操作都会被记录
+#[derive(Debug, Default, Resource, Serialize, Deserialize)]
+pub struct ActionLog {
+	pub frames: Vec<ActionFrame>,
+	#[serde(skip)]
+	recording: bool,
+}
+
+impl ActionLog {
+	/// 开始记录
+	pub fn start(&mut self) {
+		self.recording = true;
+	}
+
+	/// 停止记录
+	pub fn stop(&mut self) {
+		self.recording = false;
+	}
+
+	pub fn is_recording(&self) -> bool {
+		self.recording
+	}
+
+	pub fn to_json(&self) -> serde_json::Result<String> {
+		serde_json::to_string(self)
+	}
+
+	pub fn from_json(s: &str) -> serde_json::Result<Self> {
+		serde_json::from_str(s)
+	}
+}
+
+/// 操作回放
+/// 回放期间，每帧取出日志中的一帧，可回放类型的操作由日志提供，该类型实时写入的操作被丢弃
+#[derive(Debug, Default, Resource)]
+pub struct ActionReplay {
+	frames: VecDeque<ActionFrame>,
+}
+
+impl ActionReplay {
+	pub fn new(log: ActionLog) -> Self {
+		Self {
+			frames: log.frames.into(),
+		}
+	}
+
+	/// 回放是否已经结束
+	pub fn is_finished(&self) -> bool {
+		self.frames.is_empty()
+	}
+}
+
+// 应用一种操作时的上下文
+struct ApplyContext<'a> {
+	record: Option<&'a mut Vec<ActionRecord>>,
+	replay: Option<&'a mut Vec<ActionRecord>>,
+}
+
+struct ActionApplier {
+	priority: i32,
+	type_id: TypeId,
+	// 可回放操作注册时指定的名字，不可回放的操作为类型名
+	name: &'static str,
+	apply: fn(&mut World, &'static str, &mut ApplyContext),
+}
+
+/// 已注册的操作类型
+#[derive(Resource, Default)]
+pub struct ActionRegistry {
+	frame: u64,
+	appliers: Vec<ActionApplier>,
+}
+
+impl ActionRegistry {
+	/// 已应用的帧数
+	pub fn frame(&self) -> u64 {
+		self.frame
+	}
+
+	fn insert(&mut self, applier: ActionApplier) {
+		if self.appliers.iter().any(|r| r.type_id == applier.type_id) {
+			return;
+		}
+		// 名字用于在日志中区分操作类型，不能重复
+		if let Some(r) = self.appliers.iter().find(|r| r.name == applier.name) {
+			panic!("action name is already registered: {:?}, {:?}", r.name, applier.name);
+		}
+		// 优先级相同时，按注册顺序应用
+		let index = self.appliers.iter().position(|r| r.priority > applier.priority).unwrap_or(self.appliers.len());
+		self.appliers.insert(index, applier);
+	}
+}
+
+/// 帧边界，按优先级应用所有注册的操作
+pub fn apply_actions(world: &mut World) {
+	world.resource_scope(|world, mut registry: Mut<ActionRegistry>| {
+		registry.frame += 1;
+		let frame = registry.frame;
+
+		let recording = world.get_resource::<ActionLog>().map_or(false, |r| r.recording);
+		let mut record = Vec::new();
+		let mut replay = match world.get_resource_mut::<ActionReplay>() {
+			Some(mut r) => r.frames.pop_front().map(|r| r.actions),
+			None => None,
+		};
+
+		for applier in registry.appliers.iter() {
+			let mut context = ApplyContext {
+				record: if recording { Some(&mut record) } else { None },
+				replay: replay.as_mut(),
+			};
+			(applier.apply)(world, applier.name, &mut context);
+		}
+
+		if recording {
+			if let Some(mut log) = world.get_resource_mut::<ActionLog>() {
+				log.frames.push(ActionFrame { frame, actions: record });
+			}
+		}
+	});
+}
+
+fn coalesce<T: Action>(list: Vec<T>) -> Vec<T> {
+	let mut keys: XHashMap<u64, usize> = XHashMap::default();
+	let mut out: Vec<T> = Vec::with_capacity(list.len());
+	for action in list.into_iter() {
+		match action.key() {
+			Some(key) => match keys.get(&key) {
+				Some(index) => out[*index] = action,
+				None => {
+					keys.insert(key, out.len());
+					out.push(action);
+				}
+			},
+			None => out.push(action),
+		}
+	}
+	out
+}
+
+fn apply_list<T: Action>(world: &mut World, _name: &'static str, _context: &mut ApplyContext) {
+	let mut list = world.resource_mut::<ActionList<T>>();
+	let actions = list.drain();
+	list.frame = coalesce(actions);
+}
+
+fn apply_replayable_list<T: Action + Serialize + DeserializeOwned>(world: &mut World, name: &'static str, context: &mut ApplyContext) {
+	let mut list = world.resource_mut::<ActionList<T>>();
+	let mut actions = list.drain();
+
+	if let Some(replay) = context.replay.as_mut() {
+		actions.clear();
+		for r in replay.iter().filter(|r| r.ty == name) {
+			match serde_json::from_str::<T>(&r.data) {
+				Ok(r) => actions.push(r),
+				Err(e) => log::warn!("replay action fail, ty: {:?}, err: {:?}", name, e),
+			}
+		}
+	}
+
+	let actions = coalesce(actions);
+	if let Some(record) = context.record.as_mut() {
+		for action in actions.iter() {
+			match serde_json::to_string(action) {
+				Ok(data) => record.push(ActionRecord { ty: name.to_string(), data }),
+				Err(e) => log::warn!("record action fail, ty: {:?}, err: {:?}", name, e),
+			}
+		}
+	}
+	list.frame = actions;
+}
+
+pub trait AddAction {
+	/// 注册操作类型，操作在每帧开始时被应用
+	fn add_action<T: Action>(&mut self) -> &mut Self;
+
+	/// 注册可记录、可回放的操作类型，name为日志中该操作类型的名字，需要在各版本间保持不变、且不能与其他操作类型重复
+	fn add_replayable_action<T: Action + Serialize + DeserializeOwned>(&mut self, name: &'static str) -> &mut Self;
+}
+
+impl AddAction for App {
+	fn add_action<T: Action>(&mut self) -> &mut Self {
+		add_applier::<T>(self, std::any::type_name::<T>(), apply_list::<T>)
+	}
+
+	fn add_replayable_action<T: Action + Serialize + DeserializeOwned>(&mut self, name: &'static str) -> &mut Self {
+		add_applier::<T>(self, name, apply_replayable_list::<T>)
+	}
+}
+
+fn add_applier<T: Action>(app: &mut App, name: &'static str, apply: fn(&mut World, &'static str, &mut ApplyContext)) -> &mut App {
+	if !app.world.contains_resource::<ActionRegistry>() {
+		app.init_resource::<ActionRegistry>()
+			.init_resource::<ActionLog>()
+			.add_systems(First, apply_actions);
+	}
+	if !app.world.contains_resource::<ActionList<T>>() {
+		app.init_resource::<ActionList<T>>();
+	}
+	app.world.resource_mut::<ActionRegistry>().insert(ActionApplier {
+		priority: T::PRIORITY,
+		type_id: TypeId::of::<T>(),
+		name,
+		apply,
+	});
+	app
 }
\ No newline at end of file
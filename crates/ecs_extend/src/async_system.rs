@@ -1,644 +1,344 @@
-
-// use bevy_ecs::{
-// 	all_tuples, 
-// 	archetype::{ArchetypeComponentId, ArchetypeGeneration, ArchetypeId},
-//     change_detection::MAX_CHANGE_AGE,
-//     component::ComponentId,
-//     prelude::FromWorld,
-//     query::{Access, FilteredAccessSet},
-//     schedule::{SystemLabel, SystemLabelId},
-//     system::{
-//         ReadOnlySystemParamFetch, System, SystemParam, SystemParamFetch,
-//         SystemParamItem, SystemParamState,
-//     },
-//     world::{World, WorldId},
-// };
-// use std::{borrow::Cow, fmt::Debug, marker::PhantomData, mem::transmute, future::Future};
-
-// /// The metadata of a [`System`].
-// #[derive(Clone)]
-// pub struct SystemMeta {
-//     pub(crate) name: Cow<'static, str>,
-//     pub(crate) component_access_set: FilteredAccessSet<ComponentId>,
-//     pub(crate) archetype_component_access: Access<ArchetypeComponentId>,
-//     // NOTE: this must be kept private. making a SystemMeta non-send is irreversible to prevent
-//     // SystemParams from overriding each other
-//     is_send: bool,
-//     pub(crate) last_change_tick: u32,
-// }
-
-// impl SystemMeta {
-//     fn new<T>() -> Self {
-//         Self {
-//             name: std::any::type_name::<T>().into(),
-//             archetype_component_access: Access::default(),
-//             component_access_set: FilteredAccessSet::default(),
-//             is_send: true,
-//             last_change_tick: 0,
-//         }
-//     }
-
-//     /// Returns true if the system is [`Send`].
-//     #[inline]
-//     pub fn is_send(&self) -> bool {
-//         self.is_send
-//     }
-
-//     /// Sets the system to be not [`Send`].
-//     ///
-//     /// This is irreversible.
-//     #[inline]
-//     pub fn set_non_send(&mut self) {
-//         self.is_send = false;
-//     }
-// }
-
-// // TODO: Actually use this in AsyncFunctionSystem. We should probably only do this once Systems are constructed using a World reference
-// // (to avoid the need for unwrapping to retrieve SystemMeta)
-// /// Holds on to persistent state required to drive [`SystemParam`] for a [`System`].
-// ///
-// /// This is a very powerful and convenient tool for working with exclusive world access,
-// /// allowing you to fetch data from the [`World`] as if you were running a [`System`].
-// ///
-// /// Borrow-checking is handled for you, allowing you to mutably access multiple compatible system parameters at once,
-// /// and arbitrary system parameters (like [`EventWriter`](crate::event::EventWriter)) can be conveniently fetched.
-// ///
-// /// For an alternative approach to split mutable access to the world, see [`World::resource_scope`].
-// ///
-// /// # Warning
-// ///
-// /// [`SystemState`] values created can be cached to improve performance,
-// /// and *must* be cached and reused in order for system parameters that rely on local state to work correctly.
-// /// These include:
-// /// - [`Added`](crate::query::Added) and [`Changed`](crate::query::Changed) query filters
-// /// - [`Local`](crate::system::Local) variables that hold state
-// /// - [`EventReader`](crate::event::EventReader) system parameters, which rely on a [`Local`](crate::system::Local) to track which events have been seen
-// ///
-// /// # Example
-// ///
-// /// Basic usage:
-// /// ```rust
-// /// use bevy_ecs::prelude::*;
-// /// use bevy_ecs::{system::SystemState};
-// /// use bevy_ecs::event::Events;
-// ///
-// /// struct MyEvent;
-// /// struct MyResource(u32);
-// ///
-// /// #[derive(Component)]
-// /// struct MyComponent;
-// ///
-// /// // Work directly on the `World`
-// /// let mut world = World::new();
-// /// world.init_resource::<Events<MyEvent>>();
-// ///
-// /// // Construct a `SystemState` struct, passing in a tuple of `SystemParam`
-// /// // as if you were writing an ordinary system.
-// /// let mut system_state: SystemState<(
-// ///     EventWriter<MyEvent>,
-// ///     Option<ResMut<MyResource>>,
-// ///     Query<&MyComponent>,
-// ///     )> = SystemState::new(&mut world);
-// ///
-// /// // Use system_state.get_mut(&mut world) and unpack your system parameters into variables!
-// /// // system_state.get(&world) provides read-only versions of your system parameters instead.
-// /// let (event_writer, maybe_resource, query) = system_state.get_mut(&mut world);
-// /// ```
-// /// Caching:
-// /// ```rust
-// /// use bevy_ecs::prelude::*;
-// /// use bevy_ecs::{system::SystemState};
-// /// use bevy_ecs::event::Events;
-// ///
-// /// struct MyEvent;
-// /// struct CachedSystemState<'w, 's>{
-// ///    event_state: SystemState<EventReader<'w, 's, MyEvent>>
-// /// }
-// ///
-// /// // Create and store a system state once
-// /// let mut world = World::new();
-// /// world.init_resource::<Events<MyEvent>>();
-// /// let initial_state: SystemState<EventReader<MyEvent>>  = SystemState::new(&mut world);
-// ///
-// /// // The system state is cached in a resource
-// /// world.insert_resource(CachedSystemState{event_state: initial_state});
-// ///
-// /// // Later, fetch the cached system state, saving on overhead
-// /// world.resource_scope(|world, mut cached_state: Mut<CachedSystemState>| {
-// ///     let mut event_reader = cached_state.event_state.get_mut(world);
-// ///
-// ///     for events in event_reader.iter() {
-// ///         println!("Hello World!");
-// ///     };
-// /// });
-// /// ```
-// pub struct SystemState<Param: SystemParam> {
-//     meta: SystemMeta,
-//     param_state: <Param as SystemParam>::Fetch,
-//     world_id: WorldId,
-//     archetype_generation: ArchetypeGeneration,
-// }
-
-// impl<Param: SystemParam> SystemState<Param> {
-//     pub fn new(world: &mut World) -> Self {
-//         let mut meta = SystemMeta::new::<Param>();
-//         meta.last_change_tick = world.change_tick().wrapping_sub(MAX_CHANGE_AGE);
-//         let param_state = <Param::Fetch as SystemParamState>::init(world, unsafe { transmute(&mut meta) });
-//         Self {
-//             meta,
-//             param_state,
-//             world_id: world.id(),
-//             archetype_generation: ArchetypeGeneration::initial(),
-//         }
-//     }
-
-//     #[inline]
-//     pub fn meta(&self) -> &SystemMeta {
-//         &self.meta
-//     }
-
-//     /// Retrieve the [`SystemParam`] values. This can only be called when all parameters are read-only.
-//     #[inline]
-//     pub fn get<'w, 's>(
-//         &'s mut self,
-//         world: &'w World,
-//     ) -> <Param::Fetch as SystemParamFetch<'w, 's>>::Item
-//     where
-//         Param::Fetch: ReadOnlySystemParamFetch,
-//     {
-//         self.validate_world_and_update_archetypes(world);
-//         // SAFETY: Param is read-only and doesn't allow mutable access to World. It also matches the World this SystemState was created with.
-//         unsafe { self.get_unchecked_manual(world) }
-//     }
-
-//     /// Retrieve the mutable [`SystemParam`] values.
-//     #[inline]
-//     pub fn get_mut<'w, 's>(
-//         &'s mut self,
-//         world: &'w mut World,
-//     ) -> <Param::Fetch as SystemParamFetch<'w, 's>>::Item {
-//         self.validate_world_and_update_archetypes(world);
-//         // SAFETY: World is uniquely borrowed and matches the World this SystemState was created with.
-//         unsafe { self.get_unchecked_manual(world) }
-//     }
-
-//     /// Applies all state queued up for [`SystemParam`] values. For example, this will apply commands queued up
-//     /// by a [`Commands`](`super::Commands`) parameter to the given [`World`].
-//     /// This function should be called manually after the values returned by [`SystemState::get`] and [`SystemState::get_mut`]
-//     /// are finished being used.
-//     pub fn apply(&mut self, world: &mut World) {
-//         self.param_state.apply(world);
-//     }
-
-//     #[inline]
-//     pub fn matches_world(&self, world: &World) -> bool {
-//         self.world_id == world.id()
-//     }
-
-//     fn validate_world_and_update_archetypes(&mut self, world: &World) {
-//         assert!(self.matches_world(world), "Encountered a mismatched World. A SystemState cannot be used with Worlds other than the one it was created with.");
-//         let archetypes = world.archetypes();
-//         let new_generation = archetypes.generation();
-//         let old_generation = std::mem::replace(&mut self.archetype_generation, new_generation);
-//         let archetype_index_range = old_generation.value()..new_generation.value();
-
-//         for archetype_index in archetype_index_range {
-//             self.param_state.new_archetype(
-//                 &archetypes[ArchetypeId::new(archetype_index)],
-//                 unsafe {
-// 					transmute(&mut self.meta)
-// 				},
-//             );
-//         }
-//     }
-
-//     /// Retrieve the [`SystemParam`] values. This will not update archetypes automatically.
-//     ///
-//     /// # Safety
-//     /// This call might access any of the input parameters in a way that violates Rust's mutability rules. Make sure the data
-//     /// access is safe in the context of global [`World`] access. The passed-in [`World`] _must_ be the [`World`] the [`SystemState`] was
-//     /// created with.
-//     #[inline]
-//     pub unsafe fn get_unchecked_manual<'w, 's>(
-//         &'s mut self,
-//         world: &'w World,
-//     ) -> <Param::Fetch as SystemParamFetch<'w, 's>>::Item {
-//         let change_tick = world.increment_change_tick();
-//         let param = <Param::Fetch as SystemParamFetch>::get_param(
-//             &mut self.param_state,
-//             transmute(&self.meta),
-//             world,
-//             change_tick,
-//         );
-//         self.meta.last_change_tick = change_tick;
-//         param
-//     }
-// }
-
-// impl<Param: SystemParam> FromWorld for SystemState<Param> {
-//     fn from_world(world: &mut World) -> Self {
-//         Self::new(world)
-//     }
-// }
-
-// /// Conversion trait to turn something into a [`System`].
-// ///
-// /// Use this to get a system from a function. Also note that every system implements this trait as
-// /// well.
-// ///
-// /// # Examples
-// ///
-// /// ```
-// /// use bevy_ecs::system::IntoSystem;
-// /// use bevy_ecs::system::Res;
-// ///
-// /// fn my_system_function(an_usize_resource: Res<usize>) {}
-// ///
-// /// let system = IntoSystem::into_system(my_system_function);
-// /// ```
-// // This trait has to be generic because we have potentially overlapping impls, in particular
-// // because Rust thinks a type could impl multiple different `FnMut` combinations
-// // even though none can currently
-// pub trait IntoSystem<In, Out, Params>: Sized {
-//     type System: System<In = In, Out = Out>;
-//     /// Turns this value into its corresponding [`System`].
-//     fn into_system(this: Self) -> Self::System;
-// }
-
-// pub struct AlreadyWasSystem;
-
-// // Systems implicitly implement IntoSystem
-// impl<In, Out, Sys: System<In = In, Out = Out>> IntoSystem<In, Out, AlreadyWasSystem> for Sys {
-//     type System = Sys;
-//     fn into_system(this: Self) -> Sys {
-//         this
-//     }
-// }
-
-// /// Wrapper type to mark a [`SystemParam`] as an input.
-// ///
-// /// [`System`]s may take an optional input which they require to be passed to them when they
-// /// are being [`run`](System::run). For [`FunctionSystems`](AsyncFunctionSystem) the input may be marked
-// /// with this `In` type, but only the first param of a function may be tagged as an input. This also
-// /// means a system can only have one or zero input parameters.
-// ///
-// /// # Examples
-// ///
-// /// Here is a simple example of a system that takes a [`usize`] returning the square of it.
-// ///
-// /// ```
-// /// use bevy_ecs::prelude::*;
-// ///
-// /// fn main() {
-// ///     let mut square_system = IntoSystem::into_system(square);
-// ///
-// ///     let mut world = World::default();
-// ///     square_system.initialize(&mut world);
-// ///     assert_eq!(square_system.run(12, &mut world), 144);
-// /// }
-// ///
-// /// fn square(In(input): In<usize>) -> usize {
-// ///     input * input
-// /// }
-// /// ```
-// pub struct In<In>(pub In);
-// #[doc(hidden)]
-// pub struct InputMarker;
-
-// /// The [`System`] counter part of an ordinary function.
-// ///
-// /// You get this by calling [`IntoSystem::into_system`]  on a function that only accepts
-// /// [`SystemParam`]s. The output of the system becomes the functions return type, while the input
-// /// becomes the functions [`In`] tagged parameter or `()` if no such parameter exists.
-// ///
-// /// [`AsyncFunctionSystem`] must be `.initialized` before they can be run.
-// pub struct AsyncFunctionSystem<In, Out, Param, Marker, F>
-// where
-//     Param: SystemParam,
-// {
-//     func: F,
-//     param_state: Option<Param::Fetch>,
-//     system_meta: SystemMeta,
-//     world_id: Option<WorldId>,
-//     archetype_generation: ArchetypeGeneration,
-//     // NOTE: PhantomData<fn()-> T> gives this safe Send/Sync impls
-//     marker: PhantomData<fn() -> (In, Out, Marker)>,
-// }
-
-// pub struct IsFunctionSystem;
-
-// impl<In, Out, Param, Marker, F> IntoSystem<In, Out, (IsFunctionSystem, Param, Marker)> for F
-// where
-//     In: 'static,
-//     Param: SystemParam + 'static,
-// 	Out: Future + 'static,
-//     Marker: 'static,
-//     F: SystemParamFunction<In, Out, Param, Marker> + Send + Sync + 'static,
-// {
-//     type System = AsyncFunctionSystem<In, Out, Param, Marker, F>;
-//     fn into_system(func: Self) -> Self::System {
-//         AsyncFunctionSystem {
-//             func,
-//             param_state: None,
-//             system_meta: SystemMeta::new::<F>(),
-//             world_id: None,
-//             archetype_generation: ArchetypeGeneration::initial(),
-//             marker: PhantomData,
-//         }
-//     }
-// }
-
-// impl<In, Out, Param, Marker, F> AsyncFunctionSystem<In, Out, Param, Marker, F>
-// where
-//     Param: SystemParam,
-// {
-//     /// Message shown when a system isn't initialised
-//     // When lines get too long, rustfmt can sometimes refuse to format them.
-//     // Work around this by storing the message separately.
-//     const PARAM_MESSAGE: &'static str = "System's param_state was not found. Did you forget to initialize this system before running it?";
-// }
-
-// impl<In, Out: Future, Param, Marker, F> System for AsyncFunctionSystem<In, Out, Param, Marker, F>
-// where
-//     In: 'static,
-//     Out: 'static,
-//     Param: SystemParam + 'static,
-//     Marker: 'static,
-//     F: SystemParamFunction<In, Out, Param, Marker> + Send + Sync + 'static,
-// {
-//     type In = In;
-//     type Out = Out;
-
-//     #[inline]
-//     fn name(&self) -> Cow<'static, str> {
-//         self.system_meta.name.clone()
-//     }
-
-//     #[inline]
-//     fn component_access(&self) -> &Access<ComponentId> {
-//         self.system_meta.component_access_set.combined_access()
-//     }
-
-//     #[inline]
-//     fn archetype_component_access(&self) -> &Access<ArchetypeComponentId> {
-//         &self.system_meta.archetype_component_access
-//     }
-
-//     #[inline]
-//     fn is_send(&self) -> bool {
-//         self.system_meta.is_send
-//     }
-
-//     #[inline]
-//     unsafe fn run_unsafe(&mut self, input: Self::In, world: &World) -> Self::Out {
-//         let change_tick = world.increment_change_tick();
-
-//         // Safety:
-//         // We update the archetype component access correctly based on `Param`'s requirements
-//         // in `update_archetype_component_access`.
-//         // Our caller upholds the requirements.
-//         let params = <Param as SystemParam>::Fetch::get_param(
-//             self.param_state.as_mut().expect(Self::PARAM_MESSAGE),
-//             transmute(&self.system_meta),
-//             world,
-//             change_tick,
-//         );
-//         let out = self.func.run(input, params);
-//         self.system_meta.last_change_tick = change_tick;
-//         out
-//     }
-
-//     #[inline]
-//     fn apply_buffers(&mut self, world: &mut World) {
-//         let param_state = self.param_state.as_mut().expect(Self::PARAM_MESSAGE);
-//         param_state.apply(world);
-//     }
-
-//     #[inline]
-//     fn initialize(&mut self, world: &mut World) {
-//         self.world_id = Some(world.id());
-//         self.system_meta.last_change_tick = world.change_tick().wrapping_sub(MAX_CHANGE_AGE);
-//         self.param_state = Some(<Param::Fetch as SystemParamState>::init(
-//             world,
-//             unsafe { transmute(&mut self.system_meta) },
-//         ));
-//     }
-
-//     fn update_archetype_component_access(&mut self, world: &World) {
-//         assert!(self.world_id == Some(world.id()), "Encountered a mismatched World. A System cannot be used with Worlds other than the one it was initialized with.");
-//         let archetypes = world.archetypes();
-//         let new_generation = archetypes.generation();
-//         let old_generation = std::mem::replace(&mut self.archetype_generation, new_generation);
-//         let archetype_index_range = old_generation.value()..new_generation.value();
-
-//         for archetype_index in archetype_index_range {
-//             self.param_state.as_mut().unwrap().new_archetype(
-//                 &archetypes[ArchetypeId::new(archetype_index)],
-//                 unsafe {
-// 					transmute(&mut self.system_meta)
-// 				},
-//             );
-//         }
-//     }
-
-//     #[inline]
-//     fn check_change_tick(&mut self, change_tick: u32) {
-//         check_system_change_tick(
-//             &mut self.system_meta.last_change_tick,
-//             change_tick,
-//             self.system_meta.name.as_ref(),
-//         );
-//     }
-//     fn default_labels(&self) -> Vec<SystemLabelId> {
-//         vec![self.func.as_system_label().as_label()]
-//     }
-// }
-
-// /// A [`SystemLabel`] that was automatically generated for a system on the basis of its `TypeId`.
-// pub struct SystemTypeIdLabel<T: 'static>(PhantomData<fn() -> T>);
-
-// impl<T: 'static> SystemLabel for SystemTypeIdLabel<T> {
-//     #[inline]
-//     fn as_str(&self) -> &'static str {
-//         std::any::type_name::<T>()
-//     }
-// }
-
-// impl<T> Debug for SystemTypeIdLabel<T> {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         f.debug_tuple("SystemTypeIdLabel")
-//             .field(&std::any::type_name::<T>())
-//             .finish()
-//     }
-// }
-
-// impl<T> Clone for SystemTypeIdLabel<T> {
-//     fn clone(&self) -> Self {
-//         *self
-//     }
-// }
-// impl<T> Copy for SystemTypeIdLabel<T> {}
-
-// /// A trait implemented for all functions that can be used as [`System`]s.
-// ///
-// /// This trait can be useful for making your own systems which accept other systems,
-// /// sometimes called higher order systems.
-// ///
-// /// This should be used in combination with [`ParamSet`] when calling other systems
-// /// within your system.
-// /// Using [`ParamSet`] in this case avoids [`SystemParam`] collisions.
-// ///
-// /// # Example
-// ///
-// /// To create something like [`ChainSystem`], but in entirely safe code.
-// ///
-// /// ```rust
-// /// use std::num::ParseIntError;
-// ///
-// /// use bevy_ecs::prelude::*;
-// /// use bevy_ecs::system::{SystemParam, SystemParamItem};
-// ///
-// /// // Unfortunately, we need all of these generics. `A` is the first system, with its
-// /// // parameters and marker type required for coherence. `B` is the second system, and
-// /// // the other generics are for the input/output types of `A` and `B`.
-// /// /// Chain creates a new system which calls `a`, then calls `b` with the output of `a`
-// /// pub fn chain<AIn, Shared, BOut, A, AParam, AMarker, B, BParam, BMarker>(
-// ///     mut a: A,
-// ///     mut b: B,
-// /// ) -> impl FnMut(In<AIn>, ParamSet<(SystemParamItem<AParam>, SystemParamItem<BParam>)>) -> BOut
-// /// where
-// ///     // We need A and B to be systems, add those bounds
-// ///     A: SystemParamFunction<AIn, Shared, AParam, AMarker>,
-// ///     B: SystemParamFunction<Shared, BOut, BParam, BMarker>,
-// ///     AParam: SystemParam,
-// ///     BParam: SystemParam,
-// /// {
-// ///     // The type of `params` is inferred based on the return of this function above
-// ///     move |In(a_in), mut params| {
-// ///         let shared = a.run(a_in, params.p0());
-// ///         b.run(shared, params.p1())
-// ///     }
-// /// }
-// ///
-// /// // Usage example for `chain`:
-// /// fn main() {
-// ///     let mut world = World::default();
-// ///     world.insert_resource(Message("42".to_string()));
-// ///
-// ///     // chain the `parse_message_system`'s output into the `filter_system`s input
-// ///     let mut chained_system = IntoSystem::into_system(chain(parse_message, filter));
-// ///     chained_system.initialize(&mut world);
-// ///     assert_eq!(chained_system.run((), &mut world), Some(42));
-// /// }
-// ///
-// /// struct Message(String);
-// ///
-// /// fn parse_message(message: Res<Message>) -> Result<usize, ParseIntError> {
-// ///     message.0.parse::<usize>()
-// /// }
-// ///
-// /// fn filter(In(result): In<Result<usize, ParseIntError>>) -> Option<usize> {
-// ///     result.ok().filter(|&n| n < 100)
-// /// }
-// /// ```
-// /// [`ChainSystem`]: crate::system::ChainSystem
-// /// [`ParamSet`]: crate::system::ParamSet
-// pub trait SystemParamFunction<In, Out: Future, Param: SystemParam, Marker>: Send + Sync + 'static {
-//     fn run(&mut self, input: In, param_value: SystemParamItem<Param>) -> Out;
-// }
-
-// macro_rules! impl_system_function {
-//     ($($param: ident),*) => {
-//         #[allow(non_snake_case)]
-//         impl<Out: Future, Func: Send + Sync + 'static, $($param: SystemParam),*> SystemParamFunction<(), Out, ($($param,)*), ()> for Func
-//         where
-//         for <'a> &'a mut Func:
-//                 FnMut($($param),*) -> Out +
-//                 FnMut($(SystemParamItem<$param>),*) -> Out, Out: 'static
-//         {
-//             #[inline]
-//             fn run(&mut self, _input: (), param_value: SystemParamItem< ($($param,)*)>) -> Out {
-//                 // Yes, this is strange, but `rustc` fails to compile this impl
-//                 // without using this function. It fails to recognise that `func`
-//                 // is a function, potentially because of the multiple impls of `FnMut`
-//                 #[allow(clippy::too_many_arguments)]
-//                 fn call_inner<Out, $($param,)*>(
-//                     mut f: impl FnMut($($param,)*)->Out,
-//                     $($param: $param,)*
-//                 )->Out{
-//                     f($($param,)*)
-//                 }
-//                 let ($($param,)*) = param_value;
-//                 call_inner(self, $($param),*)
-//             }
-//         }
-
-//         // #[allow(non_snake_case)]
-//         // impl<Input, Out, Func: Send + Sync + 'static, $($param: SystemParam),*> SystemParamFunction<Input, Out, ($($param,)*), InputMarker> for Func
-//         // where
-//         // for <'a> &'a mut Func:
-//         //         FnMut(In<Input>, $($param),*) -> Out +
-//         //         FnMut(In<Input>, $(<<$param as SystemParam>::Fetch as SystemParamFetch>::Item),*) -> Out, Out: 'static
-//         // {
-//         //     #[inline]
-//         //     fn run(&mut self, input: Input, param_value: SystemParamItem< ($($param,)*)>) -> Out {
-//         //         #[allow(clippy::too_many_arguments)]
-//         //         fn call_inner<Input, Out, $($param,)*>(
-//         //             mut f: impl FnMut(In<Input>, $($param,)*)->Out,
-//         //             input: In<Input>,
-//         //             $($param: $param,)*
-//         //         )->Out{
-//         //             f(input, $($param,)*)
-//         //         }
-//         //         let ($($param,)*) = param_value;
-//         //         call_inner(self, In(input), $($param),*)
-//         //     }
-//         // }
-//     };
-// }
-
-// // Note that we rely on the highest impl to be <= the highest order of the tuple impls
-// // of `SystemParam` created.
-// all_tuples!(impl_system_function, 0, 16, F);
-
-// /// Used to implicitly convert systems to their default labels. For example, it will convert
-// /// "system functions" to their [`SystemTypeIdLabel`].
-// pub trait AsSystemLabel<Marker> {
-//     fn as_system_label(&self) -> SystemLabelId;
-// }
-
-// impl<In, Out: Future, Param: SystemParam, Marker, T: SystemParamFunction<In, Out, Param, Marker>>
-//     AsSystemLabel<(In, Out, Param, Marker)> for T
-// {
-//     #[inline]
-//     fn as_system_label(&self) -> SystemLabelId {
-//         SystemTypeIdLabel::<T>(PhantomData).as_label()
-//     }
-// }
-
-// impl<T: SystemLabel> AsSystemLabel<()> for T {
-//     #[inline]
-//     fn as_system_label(&self) -> SystemLabelId {
-//         self.as_label()
-//     }
-// }
-
-// pub(crate) fn check_system_change_tick(
-//     last_change_tick: &mut u32,
-//     change_tick: u32,
-//     system_name: &str,
-// ) {
-//     let age = change_tick.wrapping_sub(*last_change_tick);
-//     // This comparison assumes that `age` has not overflowed `u32::MAX` before, which will be true
-//     // so long as this check always runs before that can happen.
-//     if age > MAX_CHANGE_AGE {
-//         log::warn!(
-//             "System '{}' has not run for {} ticks. \
-//             Changes older than {} ticks will not be detected.",
-//             system_name,
-//             age,
-//             MAX_CHANGE_AGE - 1,
-//         );
-//         *last_change_tick = change_tick.wrapping_sub(MAX_CHANGE_AGE);
-//     }
-// }
+//! 异步system
+//! 异步system是返回future的system：system同步地读取参数（克隆需要的数据），返回的future在异步运行时上执行，不阻塞帧推；
+//! future的结果（AsyncOutput）在同步点（First阶段的AsyncSyncPoint系统集）应用到World上。
+//! 同一个异步system，在上次的结果被应用之前，不会再次运行
+//! 普通system也可以通过AsyncTasks派发后台任务，结果同样在同步点应用
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc};
+use std::task::{Context, Poll};
+
+use bevy_app::{App, First, Plugin};
+use bevy_ecs::{
+	event::{Event, Events},
+	prelude::{Component, Entity, Resource, World},
+	schedule::{IntoSystemConfigs, ScheduleLabel, SystemSet},
+	system::{CommandQueue, In, IntoSystem, Res, SystemParam},
+};
+use flume::{Receiver, Sender};
+use pi_hash::XHashMap;
+use pi_share::ShareMutex;
+use pi_async_rt::prelude::AsyncRuntime;
+use pi_futures::BoxFuture;
+
+/// 异步system结果的同步点
+#[derive(Debug, Clone, Hash, SystemSet, PartialEq, Eq)]
+pub struct AsyncSyncPoint;
+
+/// 异步任务派发器，将future派发到异步运行时上执行
+/// 渲染插件会以PiAsyncRuntime创建该单例；没有时，AsyncTasksPlugin会创建一个默认的多线程运行时
+#[derive(Resource, Clone)]
+pub struct AsyncSpawner(Arc<dyn Fn(BoxFuture<'static, ()>) -> bool + Send + Sync>);
+
+impl AsyncSpawner {
+	pub fn new<A: AsyncRuntime>(rt: A) -> Self {
+		Self(Arc::new(move |task| match rt.spawn(task) {
+			Ok(_) => true,
+			Err(e) => {
+				log::warn!("spawn async task fail, err: {:?}", e);
+				false
+			}
+		}))
+	}
+
+	/// 派发任务，返回是否派发成功（失败时任务被丢弃）
+	#[inline]
+	pub fn spawn(&self, task: BoxFuture<'static, ()>) -> bool {
+		(self.0)(task)
+	}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for AsyncSpawner {
+	fn default() -> Self {
+		Self::new(pi_async_rt::rt::AsyncRuntimeBuilder::default_multi_thread(Some("pi_bevy_async_tasks"), None, None, None))
+	}
+}
+
+// 异步system的future结束（结果已应用，或被丢弃）时，允许system再次运行
+struct RunningGuard(Arc<AtomicBool>);
+
+impl Drop for RunningGuard {
+	fn drop(&mut self) {
+		self.0.store(false, Ordering::Release);
+	}
+}
+
+/// 异步system的结果，在同步点应用到World上
+pub trait AsyncOutput: Send + 'static {
+	fn apply(self, world: &mut World);
+}
+
+impl AsyncOutput for () {
+	fn apply(self, _world: &mut World) {}
+}
+
+impl AsyncOutput for CommandQueue {
+	fn apply(mut self, world: &mut World) {
+		CommandQueue::apply(&mut self, world);
+	}
+}
+
+impl AsyncOutput for Box<dyn FnOnce(&mut World) + Send> {
+	fn apply(self, world: &mut World) {
+		self(world)
+	}
+}
+
+impl<T: AsyncOutput> AsyncOutput for Option<T> {
+	fn apply(self, world: &mut World) {
+		if let Some(r) = self {
+			r.apply(world);
+		}
+	}
+}
+
+impl<T: AsyncOutput, E: std::fmt::Debug + Send + 'static> AsyncOutput for Result<T, E> {
+	fn apply(self, world: &mut World) {
+		match self {
+			Ok(r) => r.apply(world),
+			Err(e) => log::warn!("async system fail, err: {:?}", e),
+		}
+	}
+}
+
+type ApplyFn = Box<dyn FnOnce(&mut World) + Send>;
+
+/// 已完成、等待应用的异步结果
+#[derive(Resource)]
+pub struct AsyncResults {
+	sender: Sender<ApplyFn>,
+	receiver: Receiver<ApplyFn>,
+}
+
+impl Default for AsyncResults {
+	fn default() -> Self {
+		let (sender, receiver) = flume::unbounded();
+		Self { sender, receiver }
+	}
+}
+
+/// 在同步点应用已完成的异步结果
+pub fn apply_async_results(world: &mut World) {
+	let receiver = world.resource::<AsyncResults>().receiver.clone();
+	for apply in receiver.try_iter() {
+		apply(world);
+	}
+}
+
+fn init_sync_point(app: &mut App) {
+	if !app.world.contains_resource::<AsyncResults>() {
+		app.init_resource::<AsyncResults>()
+			.add_systems(First, apply_async_results.in_set(AsyncSyncPoint));
+	}
+}
+
+pub trait AddAsyncSystem {
+	/// 添加异步system，未添加AsyncTasksPlugin时会自动添加（没有AsyncSpawner时使用默认的运行时）
+	fn add_async_system<M, Fut, Out>(&mut self, schedule: impl ScheduleLabel, system: impl IntoSystem<(), Fut, M>) -> &mut Self
+	where
+		Fut: Future<Output = Out> + Send + 'static,
+		Out: AsyncOutput;
+}
+
+impl AddAsyncSystem for App {
+	fn add_async_system<M, Fut, Out>(&mut self, schedule: impl ScheduleLabel, system: impl IntoSystem<(), Fut, M>) -> &mut Self
+	where
+		Fut: Future<Output = Out> + Send + 'static,
+		Out: AsyncOutput,
+	{
+		// 同步点和AsyncSpawner由AsyncTasksPlugin提供
+		if !self.is_plugin_added::<AsyncTasksPlugin>() {
+			self.add_plugins(AsyncTasksPlugin);
+		}
+
+		// 是否有正在执行、或未应用结果的future
+		let running = Arc::new(AtomicBool::new(false));
+		let running1 = running.clone();
+		let spawn = move |In(task): In<Fut>, spawner: Res<AsyncSpawner>, results: Res<AsyncResults>| {
+			running.store(true, Ordering::Release);
+			let sender = results.sender.clone();
+			// future被丢弃（派发失败、运行时关闭、panic）时，guard同样会重置running
+			let guard = RunningGuard(running.clone());
+			let spawned = spawner.spawn(Box::pin(async move {
+				let out = task.await;
+				let _ = sender.send(Box::new(move |world: &mut World| {
+					out.apply(world);
+					drop(guard);
+				}));
+			}));
+			if !spawned {
+				running.store(false, Ordering::Release);
+			}
+		};
+
+		self.add_systems(schedule, system.pipe(spawn).run_if(move || !running1.load(Ordering::Acquire)))
+	}
+}
+
+/// 后台任务id
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TaskId(u64);
+
+struct TaskInfo {
+	// 任务关联的实体，实体销毁时任务被取消
+	entity: Option<Entity>,
+	cancelled: Arc<AtomicBool>,
+}
+
+/// 正在执行的后台任务
+#[derive(Resource, Default)]
+pub struct AsyncTaskRegistry {
+	next: AtomicU64,
+	tasks: ShareMutex<XHashMap<TaskId, TaskInfo>>,
+}
+
+impl AsyncTaskRegistry {
+	/// 任务是否还在执行（或结果尚未应用）
+	pub fn is_running(&self, id: TaskId) -> bool {
+		self.tasks.lock().contains_key(&id)
+	}
+
+	/// 取消任务，任务的结果不会被应用
+	pub fn cancel(&self, id: TaskId) -> bool {
+		match self.tasks.lock().remove(&id) {
+			Some(r) => {
+				r.cancelled.store(true, Ordering::Release);
+				true
+			}
+			None => false,
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.tasks.lock().len()
+	}
+}
+
+/// 后台任务插件
+pub struct AsyncTasksPlugin;
+
+impl Plugin for AsyncTasksPlugin {
+	fn build(&self, app: &mut App) {
+		init_sync_point(app);
+		app.init_resource::<AsyncTaskRegistry>()
+			.add_systems(First, cancel_despawned_tasks.in_set(AsyncSyncPoint).before(apply_async_results));
+	}
+
+	fn finish(&self, app: &mut App) {
+		// 其他插件（如渲染插件）没有提供派发器时，使用默认的运行时
+		if !app.world.contains_resource::<AsyncSpawner>() {
+			#[cfg(not(target_arch = "wasm32"))]
+			app.init_resource::<AsyncSpawner>();
+			#[cfg(target_arch = "wasm32")]
+			log::warn!("AsyncSpawner is not inserted, async tasks can not run");
+		}
+	}
+}
+
+/// 取消关联实体已销毁的任务
+pub fn cancel_despawned_tasks(world: &mut World) {
+	let world = &*world;
+	world.resource::<AsyncTaskRegistry>().tasks.lock().retain(|_, info| match info.entity {
+		Some(entity) if world.get_entity(entity).is_none() => {
+			info.cancelled.store(true, Ordering::Release);
+			false
+		}
+		_ => true,
+	});
+}
+
+/// 在PiAsyncRuntime上执行后台任务，任务完成后，结果在同步点以事件、组件等形式交给World
+#[derive(SystemParam)]
+pub struct AsyncTasks<'w> {
+	spawner: Res<'w, AsyncSpawner>,
+	results: Res<'w, AsyncResults>,
+	registry: Res<'w, AsyncTaskRegistry>,
+}
+
+impl<'w> AsyncTasks<'w> {
+	/// 执行任务，结果在同步点应用
+	pub fn spawn<Out: AsyncOutput>(&self, task: impl Future<Output = Out> + Send + 'static) -> TaskId {
+		self.spawn_with(None, task, |out, world| out.apply(world))
+	}
+
+	/// 执行任务，结果以事件的形式发送
+	pub fn spawn_event<E: Event>(&self, task: impl Future<Output = E> + Send + 'static) -> TaskId {
+		self.spawn_with(None, task, |event, world| {
+			match world.get_resource_mut::<Events<E>>() {
+				Some(mut events) => {
+					events.send(event);
+				}
+				None => log::warn!("event is not registered: {:?}", std::any::type_name::<E>()),
+			}
+		})
+	}
+
+	/// 执行任务，结果作为组件插入到实体上；实体销毁时任务被取消
+	pub fn spawn_insert<C: Component>(&self, entity: Entity, task: impl Future<Output = C> + Send + 'static) -> TaskId {
+		self.spawn_with(Some(entity), task, move |component, world| {
+			if let Some(mut r) = world.get_entity_mut(entity) {
+				r.insert(component);
+			}
+		})
+	}
+
+	/// 取消任务
+	#[inline]
+	pub fn cancel(&self, id: TaskId) -> bool {
+		self.registry.cancel(id)
+	}
+
+	#[inline]
+	pub fn is_running(&self, id: TaskId) -> bool {
+		self.registry.is_running(id)
+	}
+
+	fn spawn_with<T: Send + 'static>(
+		&self,
+		entity: Option<Entity>,
+		task: impl Future<Output = T> + Send + 'static,
+		apply: impl FnOnce(T, &mut World) + Send + 'static,
+	) -> TaskId {
+		let id = TaskId(self.registry.next.fetch_add(1, Ordering::Relaxed));
+		let cancelled = Arc::new(AtomicBool::new(false));
+		self.registry.tasks.lock().insert(id, TaskInfo { entity, cancelled: cancelled.clone() });
+
+		let sender = self.results.sender.clone();
+		let spawned = self.spawner.spawn(Box::pin(async move {
+			let out = match (Cancellable { task: Box::pin(task), cancelled: cancelled.clone() }).await {
+				Some(r) => r,
+				None => return,
+			};
+			let _ = sender.send(Box::new(move |world: &mut World| {
+				let removed = world.resource::<AsyncTaskRegistry>().tasks.lock().remove(&id);
+				if removed.is_none() || cancelled.load(Ordering::Acquire) {
+					return;
+				}
+				apply(out, world);
+			}));
+		}));
+		if !spawned {
+			self.registry.tasks.lock().remove(&id);
+		}
+		id
+	}
+}
+
+// 可取消的future，取消后在下一次poll时结束
+struct Cancellable<T> {
+	task: BoxFuture<'static, T>,
+	cancelled: Arc<AtomicBool>,
+}
+
+impl<T> Future for Cancellable<T> {
+	type Output = Option<T>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.cancelled.load(Ordering::Acquire) {
+			return Poll::Ready(None);
+		}
+		self.task.as_mut().poll(cx).map(Some)
+	}
+}
@@ -1,26 +1,29 @@
 use std::borrow::Cow;
 use std::fmt::Debug;
-use std::mem::replace;
+use std::mem::{replace, transmute};
 use std::time::Duration;
 use pi_time::Instant;
 use std::{collections::HashSet, io::Result as IoResult};
 
 use pi_futures::BoxFuture;
 use pi_async_rt::prelude::{AsyncRuntime, AsyncValue};
-use fixedbitset::FixedBitSet;
 use pi_slotmap::{SlotMap, DefaultKey};
 use thiserror::Error;
 
 use pi_async_graph::{async_graph, Runnble, Runner};
 use pi_graph::{DirectedGraph, DirectedGraphNode, NGraph, NGraphBuilder};
 use bevy_ecs::{
+	component::ComponentId,
 	query::Access,
-	archetype::ArchetypeComponentId,
-	world::World,
+	system::IntoSystem,
+	world::{World, unsafe_world_cell::UnsafeWorldCell},
 };
 use pi_share::{ShareMutex, ThreadSync, ThreadSend, Share};
+use pi_share::cell::TrustCell;
 use flume::{Receiver, bounded};
 
+use super::nodes::{SyncRun, AsyncRun as AsyncRunNode};
+
 #[derive(Default)]
 pub struct DispatcherMgr {
 	arr: SlotMap<DefaultKey, Box<dyn Dispatcher>>,
@@ -37,13 +40,15 @@ impl DispatcherMgr {
 		self.arr.remove(key);
 	}
 
-	pub fn run<'a>(&'a self, key: DefaultKey, is_wait: bool) -> BoxFuture<'a, ()> {
+	/// 运行指定的派发器
+	/// 返回的future完成之前，world不能被其他地方访问
+	pub fn run<'a>(&'a self, key: DefaultKey, world: &'a mut World, is_wait: bool) -> BoxFuture<'a, ()> {
 		Box::pin(async move {
 			if let Some(r) = self.arr.get(key) {
 				let (sender, receiver) = bounded::<()>(1);
 				let pre_await = {
 					let mut lock = self.pre_wait.lock();
-					if !is_wait && lock.is_some() {// 
+					if !is_wait && lock.is_some() {//
 						return;
 					}
 					replace(&mut *lock, Some(receiver))
@@ -51,7 +56,7 @@ impl DispatcherMgr {
 				if let Some(pre_wait) = pre_await {
 					let _ = pre_wait.recv_async().await;
 				}
-				r.run().await;
+				r.run(world).await;
 				if !is_wait {
 					let mut lock = self.pre_wait.lock();
 					*lock = None;
@@ -62,51 +67,91 @@ impl DispatcherMgr {
 	}
 }
 
+/// 派发器运行时使用的World
+/// 节点在构建时持有它，派发器运行时设置为当前运行的World；
+/// 运行期间只保存UnsafeWorldCell，节点通过它并发访问World，&mut World只在阶段结束（所有节点完成）后应用延迟操作时创建
+#[derive(Clone, Default)]
+pub struct DispatchWorld(Share<ShareMutex<Option<UnsafeWorldCell<'static>>>>);
+
+impl DispatchWorld {
+	fn set(&self, world: &mut World) {
+		// 安全： 派发器运行期间World被独占借用，运行结束时调用clear
+		let cell = unsafe { transmute::<UnsafeWorldCell<'_>, UnsafeWorldCell<'static>>(world.as_unsafe_world_cell()) };
+		*self.0.lock() = Some(cell);
+	}
+
+	fn clear(&self) {
+		*self.0.lock() = None;
+	}
+
+	/// 取到派发器正在运行的World
+	/// 安全： 只能在派发器运行期间调用， 并且调用者保证对World的访问不冲突
+	pub(crate) unsafe fn get(&self) -> UnsafeWorldCell<'static> {
+		(*self.0.lock()).expect("dispatcher is not running")
+	}
+
+	// 安全： 只能在阶段结束、该阶段的所有节点都已完成后调用
+	unsafe fn world_mut(&self) -> &'static mut World {
+		self.get().world_mut()
+	}
+
+	// 阶段结束，按拓扑序应用节点的延迟操作
+	fn apply(&self, g: &NGraph<usize, ExecNode>) {
+		// 安全： 阶段的所有节点都已完成，没有其他对World的访问
+		let world = unsafe { self.world_mut() };
+		for elem in g.topological_sort() {
+			g.get(elem).unwrap().value().apply(world);
+		}
+	}
+}
+
+/// system的运行耗时
+pub type SystemStatistics = Vec<(Cow<'static, str>, Duration)>;
 
 /// 派发器 接口
 pub trait Dispatcher: ThreadSync + 'static {
     /// 只有 run 方法
-    fn run<'a>(&'a self) -> BoxFuture<'a, ()>;
+    fn run<'a>(&'a self, world: &'a mut World) -> BoxFuture<'a, ()>;
 }
 
 /// 串行 派发器
-pub struct SingleDispatcher<A:  AsyncRuntime<()>>
+pub struct SingleDispatcher<A:  AsyncRuntime>
 {
     /// 异步运行时
     rt: A,
     /// 派发器 包含 一组 Stage
     vec: Share<Vec<Stage>>,
+	world: DispatchWorld,
+	// 上一次运行各system的耗时
+	statistics: Share<ShareMutex<SystemStatistics>>,
 }
 
-impl<A: AsyncRuntime<()>> SingleDispatcher<A>
+impl<A: AsyncRuntime> SingleDispatcher<A>
 {
-    pub fn init(&mut self, vec: Vec<Stage>, arrange: &World) {
-        let mut v1 = Vec::new();
-        for i in vec.into_iter() {
-            v1.push(i);
-
-            // arrange node
-            if let Some(node) = arrange.arrange() {
-                let mut stage = StageBuilder::new();
-                stage.add_node(node);
-
-                v1.push(Share::new(stage.build(arrange)))
-            }
-        }
-		self.vec =  Share::new(v1);
+    pub fn init(&mut self, vec: Vec<Stage>) {
+		self.vec =  Share::new(vec);
     }
 
-	pub fn new(rt: A) -> Self {
+	/// 创建派发器， world需要与构建阶段时使用的DispatchWorld相同
+	pub fn new(rt: A, world: DispatchWorld) -> Self {
         SingleDispatcher {
             vec: Share::new(Vec::new()),
             rt,
+			world,
+			statistics: Share::new(ShareMutex::new(Vec::new())),
         }
     }
 
+	/// 上一次运行（或正在运行）时各system的耗时，按执行完成的顺序
+	pub fn statistics(&self) -> SystemStatistics {
+		self.statistics.lock().clone()
+	}
+
     /// 执行指定阶段的指定节点
     pub fn exec(
         vec: Share<Vec<Stage>>,
-		statistics: Share<ShareMutex<Vec<(Cow<'static, str>, Duration)>>>,
+		world: DispatchWorld,
+		statistics: Share<ShareMutex<SystemStatistics>>,
         rt: A,
         mut stage_index: usize,
         mut node_index: usize,
@@ -118,19 +163,9 @@ impl<A: AsyncRuntime<()>> SingleDispatcher<A>
             let arr = g.topological_sort();
             if node_index >= arr.len() {
                 // stage结束，apply
-                for elem in arr {
-                    let node = g.get(elem).unwrap().value();
-                    node.apply();
-                }
+                world.apply(g);
                 stage_index += 1;
                 node_index = 0;
-
-				// if stage_index == vec.len()  {
-				// 	for i in statistics.lock().unwrap().iter() {
-				// 		// log::warn!("{:?}", i);
-				// 		println!("{:?}", i);
-				// 	}
-				// }
                 continue;
             }
             let node = g.get(&arr[node_index]).unwrap().value();
@@ -138,22 +173,20 @@ impl<A: AsyncRuntime<()>> SingleDispatcher<A>
             if let Some(sync) = node.is_sync() {
                 if sync {
 					let t = Instant::now();
-					// println!("start1======");
                     node.get_sync().run();
 					statistics.lock().push((node.name(), Instant::now() - t));
-					// println!("end1======");
                 } else {
                     let f = node.get_async();
                     let vec1 = vec.clone();
                     let rt1 = rt.clone();
 					let name = node.name();
-                    rt.spawn(rt.alloc(), async move {
-						// println!("start======");
+                    rt.spawn(async move {
 						let t = Instant::now();
-                        f.await.unwrap();
+                        if let Err(e) = f.await {
+							log::warn!("async system fail, system: {:?}, err: {:?}", name, e);
+						}
 						statistics.lock().push((name, Instant::now() - t));
-						// println!("end======");
-                        SingleDispatcher::exec(vec1, statistics, rt1, stage_index, node_index, wait, true);
+                        SingleDispatcher::exec(vec1, world, statistics, rt1, stage_index, node_index, wait, true);
                     })
                     .unwrap();
 
@@ -166,75 +199,84 @@ impl<A: AsyncRuntime<()>> SingleDispatcher<A>
 		if has_async {
 			wait.set(());
 		} else {
-			rt.spawn(rt.alloc(), async move {wait.set(())}).unwrap();
+			rt.spawn(async move {wait.set(())}).unwrap();
 		}
     }
 }
 
 
-impl<A: AsyncRuntime<()>> Dispatcher for SingleDispatcher<A>
+impl<A: AsyncRuntime> Dispatcher for SingleDispatcher<A>
 {
     /// 同步节点自己执行， 如果有异步节点，则用单线程运行时执行
-    fn run<'a>(&'a self) -> BoxFuture<'a, ()> {
+    fn run<'a>(&'a self, world: &'a mut World) -> BoxFuture<'a, ()> {
+		self.world.set(world);
 		Box::pin(async move {
-			let statistics = Share::new(ShareMutex::new(Vec::new()));
+			self.statistics.lock().clear();
 			let wait = pi_async_rt::prelude::AsyncValue::new();
-			Self::exec(self.vec.clone(),  statistics, self.rt.clone(), 0, 0, wait.clone(), false);
+			Self::exec(self.vec.clone(), self.world.clone(), self.statistics.clone(), self.rt.clone(), 0, 0, wait.clone(), false);
 			wait.await;
+			self.world.clear();
 		})
     }
 }
-pub struct MultiDispatcher<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(Share<MultiInner<A1, A2>>);
+pub struct MultiDispatcher<A1: AsyncRuntime, A2: AsyncRuntime>(Share<MultiInner<A1, A2>>);
 
-impl<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>> MultiDispatcher<A1, A2>
+impl<A1: AsyncRuntime, A2: AsyncRuntime> MultiDispatcher<A1, A2>
 {
+	/// 创建派发器， world需要与构建阶段时使用的DispatchWorld相同
     pub fn new(
         vec: Vec<(Stage, Option<A2>)>,
         multi: A1,
+		world: DispatchWorld,
     ) -> Self {
-        MultiDispatcher(Share::new(MultiInner::new(vec, multi)))
+        MultiDispatcher(Share::new(MultiInner::new(vec, multi, world)))
     }
 }
 
 
-impl<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>> Dispatcher for MultiDispatcher<A1, A2>
+impl<A1: AsyncRuntime, A2: AsyncRuntime> Dispatcher for MultiDispatcher<A1, A2>
 {
     /// 根据阶段是单线程还是多线程，
     /// 如果多线程阶段，同步节点和异步节点，则用多线程运行时并行执行
     /// 如果单线程阶段，同步节点自己执行， 如果有异步节点，则用单线程运行时执行
     /// 一般为了线程安全，第一个阶段都是单线程执行
-    fn run<'a>(&'a self) -> BoxFuture<'a, ()> {
+    fn run<'a>(&'a self, world: &'a mut World) -> BoxFuture<'a, ()> {
+		self.0.world.set(world);
         Box::pin(async move {
 			let c = self.0.clone();
 			// 没有任务，直接返回
 			if c.vec.len() == 0 {
+				c.world.clear();
 				return;
 			}
 			let wait = pi_async_rt::prelude::AsyncValue::new();
-			exec(c, 0, wait.clone());
+			exec(c.clone(), 0, wait.clone());
 			wait.await;
+			c.world.clear();
 		})
     }
 }
 
-struct MultiInner<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>
+struct MultiInner<A1: AsyncRuntime, A2: AsyncRuntime>
 {
     vec: Vec<(Stage, Option<A2>)>,
     multi: A1,
+	world: DispatchWorld,
 }
 
-impl<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>> MultiInner<A1, A2>
+impl<A1: AsyncRuntime, A2: AsyncRuntime> MultiInner<A1, A2>
 {
     pub fn new(
         vec: Vec<(Stage, Option<A2>)>,
         multi: A1,
+		world: DispatchWorld,
     ) -> Self {
-        MultiInner { vec, multi }
+        MultiInner { vec, multi, world }
     }
 }
 
 /// 执行指定阶段
-fn exec<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(d: Share<MultiInner<A1, A2>>, stage_index: usize, wait: AsyncValue<()>)
+fn exec<A1: AsyncRuntime, A2: AsyncRuntime>(d: Share<MultiInner<A1, A2>>, stage_index: usize, wait: AsyncValue<()>)
 {
     if stage_index >= d.vec.len() {
 		wait.set(());
@@ -249,7 +291,7 @@ fn exec<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(d: Share<MultiInner<A1, A2>>
 }
 
 /// 单线程执行, 尽量本线程运行，遇到异步节点则用单线程运行时运行
-fn single_exec<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(
+fn single_exec<A1: AsyncRuntime, A2: AsyncRuntime>(
     d: Share<MultiInner<A1, A2>>,
     stage_index: usize,
     mut node_index: usize,
@@ -263,10 +305,7 @@ fn single_exec<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(
         let arr = g.topological_sort();
         if node_index >= g.node_count() {
             // stage结束，apply
-            for elem in arr {
-                let node = g.get(elem).unwrap().value();
-                node.apply();
-            }
+            d.world.apply(g);
 
             // 本阶段执行完毕，执行下一阶段
             return exec(d, stage_index + 1, wait);
@@ -280,7 +319,7 @@ fn single_exec<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(
                     let f = node.get_sync();
                     let d1 = d.clone();
                     single1
-                        .spawn(single1.alloc(), async move {
+                        .spawn(async move {
                             f.run();
                             single_exec(d1, stage_index, node_index, single, wait);
                         })
@@ -293,7 +332,7 @@ fn single_exec<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(
                 let f = node.get_async();
                 let d1 = d.clone();
                 single1
-                    .spawn(single1.alloc(), async move {
+                    .spawn(async move {
                         let _ = f.await;
                         single_exec(d1, stage_index, node_index, single, wait);
                     })
@@ -305,20 +344,16 @@ fn single_exec<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(
 }
 
 /// 多线程执行
-fn multi_exec<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(d: Share<MultiInner<A1, A2>>, stage_index: usize, wait: AsyncValue<()>)
+fn multi_exec<A1: AsyncRuntime, A2: AsyncRuntime>(d: Share<MultiInner<A1, A2>>, stage_index: usize, wait: AsyncValue<()>)
 {
     let d1 = d.clone();
     d.multi
-        .spawn(d.multi.alloc(), async move {
+        .spawn(async move {
             let g = &d1.vec[stage_index].0;
             let r = async_graph(d1.multi.clone(), g.clone()).await;
             if r.is_ok() {
                 // stage结束，apply
-                let arr = g.topological_sort();
-                for elem in arr {
-                    let node = g.get(elem).unwrap().value();
-                    node.apply();
-                }
+                d1.world.apply(g);
 
                 exec(d1, stage_index + 1, wait);
             }
@@ -331,15 +366,23 @@ fn multi_exec<A1: AsyncRuntime<()>, A2: AsyncRuntime<()>>(d: Share<MultiInner<A1
 pub struct GraphNode {
     // 节点id，每个节点有 独一无二的 id
     pub(crate) id: usize,
-    // // 节点的输入，意义是 它 依赖 的 节点，决定 执行关系
-    // pub(crate) reads: Vec<usize>,
-    // // 节点的输出，意义是 依赖 它 的 节点，决定 执行关系
-    // pub(crate) writes: Vec<usize>,
-	pub(crate) access: Access<ArchetypeComponentId>,
+	// 节点对组件的访问，决定 执行关系
+	pub(crate) access: Access<ComponentId>,
     // 执行节点
     pub(crate) node: ExecNode,
 
 	pub(crate) label: String,
+	// 节点会在其他线程上运行， 非Send的system不能加入
+	pub(crate) is_send: bool,
+	// 独占system需要&mut World， 不能与其他节点并发
+	pub(crate) is_exclusive: bool,
+}
+
+impl GraphNode {
+	/// 节点id，可用于StageBuilder::order
+	pub fn id(&self) -> usize {
+		self.id
+	}
 }
 
 /// 操作
@@ -349,7 +392,7 @@ pub trait Operate: ThreadSend + 'static {
 
     /// 执行
     /// 执行结果，会缓冲到 内部，等当前Stage全部执行结束后，再统一调用apply，刷新到world上
-    fn run(&self, world: &mut World) -> Self::R;
+    fn run(&self) -> Self::R;
 
     /// 应用
     /// 在该stage所有的system run 结束之后 执行
@@ -423,10 +466,10 @@ impl Runnble for ExecNode {
 }
 
 impl ExecNode {
-    fn apply(&self) {
+    fn apply(&self, world: &mut World) {
         match self {
-            ExecNode::Sync(f) => f.0.apply(),
-            ExecNode::Async(f) => f.0.apply(),
+            ExecNode::Sync(f) => f.0.apply(world),
+            ExecNode::Async(f) => f.0.apply(world),
             _ => (),
         };
     }
@@ -440,16 +483,15 @@ impl ExecNode {
 	}
 }
 
-pub trait Arrange {
-    fn arrange(&self) -> Option<GraphNode>;
-}
-
 /// Stage 是 由 可执行节点 组成的 图
-type Stage = Share<NGraph<usize, ExecNode>>;
+pub type Stage = Share<NGraph<usize, ExecNode>>;
+
+// system节点的id从该值开始分配，之前的id为组件节点（组件id）
+const SYSTEM_ID_START: usize = usize::MAX >> 1;
 
 /// 阶段构造器
-#[derive(Default)]
 pub struct StageBuilder {
+	world: DispatchWorld,
     // 所有的节点 id
     components: HashSet<usize>,
     // 节点
@@ -459,9 +501,14 @@ pub struct StageBuilder {
 }
 
 impl StageBuilder {
-    /// 创建
-    pub fn new() -> Self {
-        StageBuilder::default()
+    /// 创建， 阶段中的节点在运行时通过world访问World
+    pub fn new(world: DispatchWorld) -> Self {
+        StageBuilder {
+			world,
+			components: HashSet::default(),
+			systems: Vec::new(),
+			edges: Vec::new(),
+		}
     }
 
     /// 加入节点
@@ -474,6 +521,45 @@ impl StageBuilder {
         self
     }
 
+	/// 加入同步system
+	pub fn add_system<M>(&mut self, world: &mut World, system: impl IntoSystem<(), (), M>) -> &mut Self {
+		let mut system = IntoSystem::into_system(system);
+		system.initialize(world);
+		let access = system.component_access().clone();
+		let label = system.name().to_string();
+		let node = GraphNode {
+			id: self.next_id(),
+			access,
+			is_send: system.is_send(),
+			is_exclusive: system.is_exclusive(),
+			node: ExecNode::Sync(Run(Share::new(SyncRun::new(Box::new(system), self.world.clone())))),
+			label,
+		};
+		self.add_node(node)
+	}
+
+	/// 加入异步system， system返回的future在运行时上执行， 本阶段的后续节点在future完成后执行
+	/// 注意： future是'static的， 不能借用system的参数， 需要的数据应在system中克隆出来
+	pub fn add_async_system<M>(&mut self, world: &mut World, system: impl IntoSystem<(), BoxFuture<'static, IoResult<()>>, M>) -> &mut Self {
+		let mut system = IntoSystem::into_system(system);
+		system.initialize(world);
+		let access = system.component_access().clone();
+		let label = system.name().to_string();
+		let node = GraphNode {
+			id: self.next_id(),
+			access,
+			is_send: system.is_send(),
+			is_exclusive: system.is_exclusive(),
+			node: ExecNode::Async(AsyncRun(Share::new(AsyncRunNode::new(Share::new(TrustCell::new(Box::new(system))), self.world.clone())))),
+			label,
+		};
+		self.add_node(node)
+	}
+
+	fn next_id(&self) -> usize {
+		SYSTEM_ID_START + self.systems.len()
+	}
+
 	/// 取到刚添加的最后一个节点
 	pub fn get_last_node(&self) -> Option<&GraphNode> {
 		let len = self.systems.len();
@@ -493,39 +579,47 @@ impl StageBuilder {
     }
 
     /// 构建 拓扑 序
-    pub fn build(mut self, world: &World) -> NGraph<usize, ExecNode> {
+	/// 读组件的system依赖写该组件的system； 同一组件存在多个写system时，按加入顺序依次执行
+	/// 节点可能在其他线程上并发运行， 非Send的system和独占system会使构建失败
+    pub fn build(mut self, _world: &World) -> Result<NGraph<usize, ExecNode>, BuildErr> {
         // Stages --> NGraph
         let mut builder = NGraphBuilder::new();
 
+		// (组件id, 最后一个写该组件的system id)
+		let mut writers: Vec<(usize, usize)> = Vec::new();
 		for s in self.systems.iter() {
-			match write_depend(world, s.access.get_reads_and_writes(), s.access.get_writes(), s.access.get_modify()) {
-				Ok((mut r, w)) => {
-					r.difference_with(&w);
-
-					// log::warn!("system: {:?}", s.node.name());
-					// 边: 该节点 --> 输出
-					for k in r.ones() {
-					    self.components.insert(k);
-					    self.edges.push((k, s.id));
-						// log::warn!("read: {:?}", &world.archetypes().archetype_component_info[k]);
-					}
+			if s.is_exclusive {
+				return Err(BuildErr::Exclusive(s.label.clone()));
+			}
+			if !s.is_send {
+				return Err(BuildErr::NotSend(s.label.clone()));
+			}
+			if s.access.reads_all() {
+				return Err(BuildErr::ReadAll(s.label.clone()));
+			}
+			// 边: 输入 --> 该节点
+			for k in s.access.reads() {
+				let k = k.index();
+				self.components.insert(k);
+				self.edges.push((k, s.id));
+			}
 
-					for k in w.ones() {
-					    self.components.insert(k);
-					    self.edges.push((s.id, k));
-						// log::warn!("write: {:?}", &world.archetypes().archetype_component_info[k]);
-					}
-				},
-				Err(c) => {
-					let c: Vec<&'static str> = c.ones().map(|i| {(*&world.archetypes().archetype_component_info[i]).clone()}).collect();
-					panic!("{:?}", BuildErr::WriteConflict(s.label.clone(), c));
+			// 边: 该节点 --> 输出
+			for k in s.access.writes() {
+				let k = k.index();
+				match writers.iter_mut().find(|r| r.0 == k) {
+					// 边: 前一个写system --> 该节点
+					Some(r) => self.edges.push((replace(&mut r.1, s.id), s.id)),
+					None => writers.push((k, s.id)),
 				}
+				self.components.insert(k);
+				self.edges.push((s.id, k));
 			}
 		}
 
         for id in self.components {
             // 每个 Component 都是一个节点
-            builder = builder.node(id, ExecNode::None(world.archetypes().archetype_component_info[id]));
+            builder = builder.node(id, ExecNode::None("component"));
         }
 
         for n in self.systems {
@@ -538,28 +632,18 @@ impl StageBuilder {
             builder = builder.edge(n.0, n.1);
         }
 
-		builder.build().unwrap()
+		builder.build().map_err(|_| BuildErr::Circly)
     }
 }
 
 #[derive(Debug, Error)]
 pub enum BuildErr {
-	#[error("build fail, node is circly: {0:?}")]
-	Circly(Vec<usize>),
-	#[error("build fail, write conflict, system: {0:?}, write access {1:?}")]
-	WriteConflict(String, Vec<&'static str>)
+	#[error("build fail, node is circly")]
+	Circly,
+	#[error("build fail, system read all world, system: {0:?}")]
+	ReadAll(String),
+	#[error("build fail, system is not send, system: {0:?}")]
+	NotSend(String),
+	#[error("build fail, system is exclusive, system: {0:?}")]
+	Exclusive(String),
 }
-
-
-fn write_depend(w: &World, read_writes: &FixedBitSet, writes: &FixedBitSet, modifys: &FixedBitSet) -> Result<(FixedBitSet, FixedBitSet), FixedBitSet> {
-	Ok((read_writes, writes))
-	// let (mut read_writes_new, mut write_new) = (read_writes.clone(), writes.clone());
-	// let conflict = FixedBitSet::default();
-
-	// if conflict.count_ones(..) > 0 {
-	// 	println!("len: {:?}, {:?}", conflict.count_ones(..), conflict);
-	// 	Err(conflict)
-	// } else {
-	// 	Ok((read_writes_new, write_new))
-	// }
-}
\ No newline at end of file
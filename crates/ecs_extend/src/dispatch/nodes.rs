@@ -1,159 +1,84 @@
-use super::interface::Run;
-use crate::dispatch::interface::{Arrange, ExecNode, GraphNode, Operate};
-use bevy_ecs::{
-    system::{SystemParam, FunctionSystem, SystemParamFunction, System},
-	query::Access,
-    world::World,
-};
-use std::future::Future;
+use super::interface::{DispatchWorld, Operate};
+use bevy_ecs::{system::System, world::World};
 use pi_futures::BoxFuture;
 use pi_share::cell::TrustCell;
 use std::borrow::Cow;
 use std::io::Result;
-use pi_share::{ThreadSend, Share};
+use pi_share::Share;
 
-pub struct SyncRun<Param: SystemParam + 'static, Out, F>(
-    pub(crate) TrustCell<FunctionSystem<(), Out, Param, (), F>>,
-);
+/// 同步system节点
+pub struct SyncRun {
+	system: TrustCell<Box<dyn System<In = (), Out = ()>>>,
+	world: DispatchWorld,
+}
 
-pub struct AsyncRun<Param: SystemParam + 'static, Out: Future<Output=Result<()>> + ThreadSend + 'static, F: ThreadSend + 'static>(
-    pub(crate) Share<TrustCell<FunctionSystem<(), Out, Param, (), F>>>,
-);
+impl SyncRun {
+	pub fn new(system: Box<dyn System<In = (), Out = ()>>, world: DispatchWorld) -> Self {
+		Self {
+			system: TrustCell::new(system),
+			world,
+		}
+	}
+}
 
-unsafe impl<Param, Out, F> Send for AsyncRun<Param, Out, F> where 
-	Param: SystemParam + 'static, 
-	Out: Future<Output=Result<()>> + ThreadSend + 'static, 
-	F: ThreadSend + 'static {}
-unsafe impl<Param, Out, F> Sync for AsyncRun<Param, Out, F> where 
-Param: SystemParam + 'static, 
-Out: Future<Output=Result<()>> + ThreadSend + 'static, 
-F: ThreadSend + 'static {}
+/// 异步system节点， system返回一个future
+pub struct AsyncRun {
+	system: Share<TrustCell<Box<dyn System<In = (), Out = BoxFuture<'static, Result<()>>>>>>,
+	world: DispatchWorld,
+}
 
-impl<Param: SystemParam + 'static, Out: ThreadSend + 'static, F> Operate for SyncRun<Param, Out, F>
-where
-    F: SystemParamFunction<(), Out, Param, ()> + ThreadSend + 'static,
-{
+impl AsyncRun {
+	pub fn new(system: Share<TrustCell<Box<dyn System<In = (), Out = BoxFuture<'static, Result<()>>>>>>, world: DispatchWorld) -> Self {
+		Self { system, world }
+	}
+}
+
+// StageBuilder::build拒绝了非Send和独占的system， TrustCell保证同一时间只有一个借用
+unsafe impl Send for SyncRun {}
+unsafe impl Sync for SyncRun {}
+unsafe impl Send for AsyncRun {}
+unsafe impl Sync for AsyncRun {}
+
+impl Operate for SyncRun {
     type R = ();
 
     fn run(&self) {
-		// log::warn!("run============{:?}", std::any::type_name::<F>());
-		
-        self.0.borrow_mut().run(());
-		// println!("run end============{:?}", std::any::type_name::<F>());
+		let mut system = self.system.borrow_mut();
+		// 安全： 派发器保证同时运行的system访问不冲突
+		unsafe {
+			let world = self.world.get();
+			system.update_archetype_component_access(world);
+			system.run_unsafe((), world);
+		}
     }
 
-    fn apply(&self) {
-        self.0.borrow_mut().apply_buffers();
+    fn apply(&self, world: &mut World) {
+        self.system.borrow_mut().apply_deferred(world);
     }
 
 	fn name(&self) -> Cow<'static, str> {
-        self.0.borrow().name()
+        self.system.borrow().name()
     }
 }
 
-impl<Param, Out, F> Operate for AsyncRun<Param, Out, F>
-where
-    F: SystemParamFunction<(), Out, Param, ()> + ThreadSend + 'static,
-	Out: Future<Output = Result<()>  > + ThreadSend + 'static,
-	Param: SystemParam + 'static
-{
+impl Operate for AsyncRun {
     type R = BoxFuture<'static, Result<()>>;
 
     fn run(&self) -> BoxFuture<'static, Result<()>> {
-		// log::warn!("async============{:?}", std::any::type_name::<F>());
-		let context: AsyncRun<Param, Out, F> = Self(self.0.clone());
-		Box::pin(async move {
-			// 将context捕获，使得future在执行时，system始终存在，保证future执行的安全性
-			let mut b = context.0.borrow_mut();
-			b.run(()).await
-		})
-    }
-    fn apply(&self) {
-        self.0.borrow_mut().apply_buffers();
-    }
-
-	fn name(&self) -> Cow<'static, str> {
-        self.0.borrow().name()
-    }
-}
-
-impl<Param: SystemParam + 'static, Out: ThreadSend + 'static, F> Into<GraphNode> for FunctionSystem<(), Out, Param, (), F>
-where
-    F: SystemParamFunction<(), Out, Param, ()> + ThreadSend + 'static,
-{
-    default fn into(self) -> GraphNode {
-        let id = self.id();
-		let name = self.name().to_string();
-        let component_access = self.archetype_component_access().clone();
-
-        let sys = TrustCell::new(self);
-        GraphNode {
-            id: id.id(),
-            // reads,
-            // writes,
-            node: ExecNode::Sync(Run(Share::new(SyncRun(sys)))),
-			access: component_access,
-			label: name,
-        }
+		let mut system = self.system.borrow_mut();
+		// 安全： 派发器保证同时运行的system访问不冲突； future是'static的，不借用system的参数
+		unsafe {
+			let world = self.world.get();
+			system.update_archetype_component_access(world);
+			system.run_unsafe((), world)
+		}
     }
-}
-
-impl<Param, Out, F> Into<GraphNode>
-    for FunctionSystem<(), Out, Param, (), F>
-where
-    F: SystemParamFunction<(), Out, Param, ()> + ThreadSend + 'static,
-	Param: SystemParam + 'static,
-	Out: Future<Output = Result<()>> + ThreadSend + 'static
-{
-    fn into(self) -> GraphNode {
-        let id = self.id();
-		let name = self.name().to_string();
-        let component_access = self.archetype_component_access().clone();
-
-        let sys = Share::new(TrustCell::new(self));
-        GraphNode {
-            id: id.id(),
-            node: ExecNode::Async(super::interface::AsyncRun(Share::new(AsyncRun(sys)))),
-			access: component_access,
-			label: name,
-        }
-    }
-}
-
-impl Arrange for World {
-    fn arrange(&self) -> Option<GraphNode> {
-        let mut w = self.clone();
-        let id = w.archetype_component_grow("arrange", false);
-        let sys = move || {
-			for l in w.listeners.iter() {
-				l.apply();
-			}
-            w.increment_change_tick();
-        };
-        Some(GraphNode {
-            id,
-            node: ExecNode::Sync(Run(Share::new(FnSys(Box::new(sys))))),
-			access: Access::default(),
-			label: "increment_change_tick".to_string(),
-        })
-    }
-}
-
-pub trait FnSysTrait: Fn() + ThreadSend + 'static {}
-impl<T: Fn() + ThreadSend + 'static> FnSysTrait for T {}
 
-pub struct FnSys(pub(crate) Box<dyn FnSysTrait>);
-
-impl Operate for FnSys {
-    type R = ();
-
-    fn run(&self) {
-        self.0();
+    fn apply(&self, world: &mut World) {
+        self.system.borrow_mut().apply_deferred(world);
     }
-    fn apply(&self) {}
 
 	fn name(&self) -> Cow<'static, str> {
-		"FnSys".into()
+        self.system.borrow().name()
     }
 }
-
@@ -0,0 +1,254 @@
+//! 撤销、重做
+//! 可逆操作通过ActionWriter<HistoryAction>提交，在帧边界应用到World上，并按事务记录到History中
+//! 未显式开启事务时，一帧内提交的所有可逆操作组成一个事务
+
+use std::mem::replace;
+
+use bevy_app::{App, First, Plugin};
+use bevy_ecs::{
+	event::Events,
+	prelude::{Component, Entity, Resource, World},
+	query::Changed,
+	schedule::IntoSystemConfigs,
+	world::Mut,
+};
+
+use crate::{
+	action::{apply_actions, Action, ActionList, AddAction},
+	system_param::layer_dirty::ComponentEvent,
+};
+
+/// 可逆操作
+pub trait ReversibleAction: Send + Sync + 'static {
+	/// 应用到World上
+	fn apply(&mut self, world: &mut World);
+	/// 撤销对World的修改
+	fn revert(&mut self, world: &mut World);
+}
+
+/// 历史操作
+pub enum HistoryAction {
+	/// 应用一个可逆操作，并记录到当前事务中
+	Do(Box<dyn ReversibleAction>),
+	/// 撤销最近的一个事务，未结束的显式事务会先被结束
+	Undo,
+	/// 重做最近撤销的一个事务
+	Redo,
+	/// 开启一个显式事务，直到End之前提交的可逆操作都属于该事务
+	Begin(String),
+	/// 结束显式事务
+	End,
+}
+
+impl Action for HistoryAction {}
+
+/// 事务
+pub struct Transaction {
+	pub name: Option<String>,
+	actions: Vec<Box<dyn ReversibleAction>>,
+}
+
+impl Transaction {
+	pub fn len(&self) -> usize {
+		self.actions.len()
+	}
+
+	fn apply(&mut self, world: &mut World) {
+		for action in self.actions.iter_mut() {
+			action.apply(world);
+		}
+	}
+
+	fn revert(&mut self, world: &mut World) {
+		for action in self.actions.iter_mut().rev() {
+			action.revert(world);
+		}
+	}
+}
+
+/// 历史记录
+#[derive(Resource)]
+pub struct History {
+	undo: Vec<Transaction>,
+	redo: Vec<Transaction>,
+	// 显式开启的事务
+	scope: Option<Transaction>,
+	// 最多保留的事务数量
+	max_len: usize,
+}
+
+impl Default for History {
+	fn default() -> Self {
+		Self {
+			undo: Vec::new(),
+			redo: Vec::new(),
+			scope: None,
+			max_len: 100,
+		}
+	}
+}
+
+impl History {
+	pub fn new(max_len: usize) -> Self {
+		Self { max_len, ..Default::default() }
+	}
+
+	pub fn can_undo(&self) -> bool {
+		self.undo.len() > 0
+	}
+
+	pub fn can_redo(&self) -> bool {
+		self.redo.len() > 0
+	}
+
+	/// 可撤销的事务（从旧到新）
+	pub fn undo_list(&self) -> &[Transaction] {
+		&self.undo
+	}
+
+	/// 可重做的事务（从新到旧）
+	pub fn redo_list(&self) -> &[Transaction] {
+		&self.redo
+	}
+
+	pub fn clear(&mut self) {
+		self.undo.clear();
+		self.redo.clear();
+		self.scope = None;
+	}
+
+	fn push(&mut self, transaction: Transaction) {
+		if transaction.actions.len() == 0 {
+			return;
+		}
+		self.undo.push(transaction);
+		if self.undo.len() > self.max_len {
+			self.undo.remove(0);
+		}
+	}
+}
+
+/// 撤销、重做插件
+pub struct HistoryPlugin {
+	pub max_len: usize,
+}
+
+impl Default for HistoryPlugin {
+	fn default() -> Self {
+		Self { max_len: 100 }
+	}
+}
+
+impl Plugin for HistoryPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_action::<HistoryAction>()
+			.insert_resource(History::new(self.max_len))
+			.add_systems(First, apply_history.after(apply_actions));
+	}
+}
+
+/// 应用本帧的历史操作
+pub fn apply_history(world: &mut World) {
+	let actions = world.resource_mut::<ActionList<HistoryAction>>().take_frame();
+	if actions.len() == 0 {
+		return;
+	}
+
+	world.resource_scope(|world, mut history: Mut<History>| {
+		// 本帧隐式事务
+		let mut frame = Transaction { name: None, actions: Vec::new() };
+		for action in actions.into_iter() {
+			match action {
+				HistoryAction::Do(mut r) => {
+					r.apply(world);
+					match &mut history.scope {
+						Some(scope) => scope.actions.push(r),
+						None => frame.actions.push(r),
+					}
+					history.redo.clear();
+				}
+				HistoryAction::Undo => {
+					// 先提交本帧已经应用的操作，并结束未结束的显式事务，保证撤销的是最近的修改
+					let done = replace(&mut frame, Transaction { name: None, actions: Vec::new() });
+					history.push(done);
+					if let Some(scope) = history.scope.take() {
+						history.push(scope);
+					}
+					if let Some(mut r) = history.undo.pop() {
+						r.revert(world);
+						history.redo.push(r);
+					}
+				}
+				HistoryAction::Redo => {
+					if let Some(mut r) = history.redo.pop() {
+						r.apply(world);
+						history.push(r);
+					}
+				}
+				HistoryAction::Begin(name) => {
+					// 先提交本帧在事务开启前应用的操作，保证撤销顺序与应用顺序相反
+					let done = replace(&mut frame, Transaction { name: None, actions: Vec::new() });
+					history.push(done);
+					if let Some(scope) = history.scope.take() {
+						history.push(scope);
+					}
+					history.scope = Some(Transaction { name: Some(name), actions: Vec::new() });
+				}
+				HistoryAction::End => {
+					if let Some(scope) = history.scope.take() {
+						history.push(scope);
+					}
+				}
+			}
+		}
+		history.push(frame);
+	});
+}
+
+/// 为实体发送组件修改事件（LayerDirty通过该事件感知修改）
+pub fn notify_changed<T: Component>(world: &mut World, entity: Entity) {
+	if let Some(mut events) = world.get_resource_mut::<Events<ComponentEvent<Changed<T>>>>() {
+		events.send(ComponentEvent::new(entity));
+	}
+}
+
+/// 设置组件的可逆操作，撤销时恢复原值（原来不存在则删除组件）
+pub struct SetComponent<T: Component + Clone> {
+	pub entity: Entity,
+	pub value: T,
+	old: Option<T>,
+}
+
+impl<T: Component + Clone> SetComponent<T> {
+	pub fn new(entity: Entity, value: T) -> Self {
+		Self { entity, value, old: None }
+	}
+}
+
+impl<T: Component + Clone> ReversibleAction for SetComponent<T> {
+	fn apply(&mut self, world: &mut World) {
+		let mut entity = match world.get_entity_mut(self.entity) {
+			Some(r) => r,
+			None => return,
+		};
+		self.old = entity.get::<T>().cloned();
+		entity.insert(self.value.clone());
+		notify_changed::<T>(world, self.entity);
+	}
+
+	fn revert(&mut self, world: &mut World) {
+		let mut entity = match world.get_entity_mut(self.entity) {
+			Some(r) => r,
+			None => return,
+		};
+		match self.old.take() {
+			Some(old) => {
+				entity.insert(old);
+			}
+			None => {
+				entity.remove::<T>();
+			}
+		};
+		notify_changed::<T>(world, self.entity);
+	}
+}
@@ -18,17 +18,25 @@ use derive_deref::Deref;
 pub mod system_param;
 pub mod query;
 pub mod async_system;
-// pub mod dispatch;
+pub mod dispatch;
 pub mod action;
+pub mod history;
+pub mod shell;
+pub mod timing;
 
 
 pub mod prelude {
     pub use crate::{
         system_param::{
 			tree::{Layer, Down, Up, EntityTreeMut, EntityTree, Root},
-			layer_dirty::LayerDirty
+			layer_dirty::LayerDirty,
+			flat_tree::{FlatTree, FlatTreePlugin},
 		},
-		query::or_default::{OrDefault, DefaultComponent},
+		query::{or_default::{OrDefault, DefaultComponent, ChangedOrDefault}, or_default_mut::OrDefaultMut},
+		action::{Action, ActionList, ActionWriter, ActionReader, ActionLog, ActionReplay, AddAction},
+		history::{ReversibleAction, HistoryAction, History, HistoryPlugin, SetComponent},
+		async_system::{AddAsyncSystem, AsyncOutput, AsyncSpawner, AsyncSyncPoint, AsyncTasks, AsyncTasksPlugin, TaskId},
+		timing::{TimingPlugin, SystemTimings, TimedSystem, AddTimedSystem, TimedMain},
 		
     };
 }
@@ -40,5 +48,6 @@ pub trait TShell {
 	fn app_mut(&mut self) -> &mut App;
 }
 
+#[deprecated(note = "use `pi_bevy_window::FrameControl` to pause or step frames")]
 #[derive(Resource, Debug, Deref, Default)]
 pub struct IsNotRun(pub bool);
\ No newline at end of file
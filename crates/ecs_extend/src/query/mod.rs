@@ -1 +1,2 @@
-pub mod or_default;
\ No newline at end of file
+pub mod or_default;
+pub mod or_default_mut;
\ No newline at end of file
@@ -2,12 +2,13 @@
 
 use std::marker::PhantomData;
 
-use bevy_ecs::{query::{Access, FilteredAccess, WorldQuery, ReadFetch, ReadOnlyWorldQuery}, component::{StorageType, ComponentStorage, ComponentId, Tick}, archetype::{Archetype, ArchetypeComponentId}, world::{ World, FromWorld, unsafe_world_cell::UnsafeWorldCell}, storage::{Table, TableRow}, prelude::{Component, Entity}, system::Resource};
+use bevy_ecs::{query::{Access, FilteredAccess, WorldQuery, ReadFetch, ReadOnlyWorldQuery, Changed}, component::{StorageType, ComponentStorage, ComponentId, Tick}, archetype::{Archetype, ArchetypeComponentId}, world::{ World, FromWorld, unsafe_world_cell::UnsafeWorldCell}, storage::{Table, TableRow}, prelude::{Component, Entity}, system::Resource};
 use derive_deref::{Deref, DerefMut};
 
 /// 不存在T时，使用默认值。
 /// 默认值取单例DefaultValue<T>
 /// DefaultValue<T>默认为DefaultValue::from_world的返回值，也可被应用程序覆盖
+/// 如果需要感知默认值的修改，应使用过滤器ChangedOrDefault<T>代替Changed<T>
 pub struct OrDefault<T> {
 	// value: &'w T,
 	mark: PhantomData<T>,
@@ -124,19 +125,7 @@ unsafe impl<T: Component + FromWorld> WorldQuery for OrDefault<T> {
 	}
 
     fn init_state(world: &mut World) -> OrDefaultState {
-
-		if let None = world.get_resource::<DefaultComponent<T>>() {
-			let v = DefaultComponent(T::from_world(world));
-			world.insert_resource(v);
-		}
-		let default_res_component_id = world.components().get_resource_id(std::any::TypeId::of::<DefaultComponent<T>>()).unwrap();
-		let default_res_archetype_component_id = world.storages().resources.get(default_res_component_id).unwrap().id();
-
-		OrDefaultState {
-			component_state: world.init_component::<T>(),
-			default_res_component_id: default_res_component_id,
-			default_res_archetype_component_id: default_res_archetype_component_id,
-		}
+		init_or_default_state::<T>(world)
     }
 
     fn matches_component_set(
@@ -156,4 +145,150 @@ pub struct OrDefaultFetch<'w, T> {
 	matches: bool,
 }
 
+fn init_or_default_state<T: Component + FromWorld>(world: &mut World) -> OrDefaultState {
+	if let None = world.get_resource::<DefaultComponent<T>>() {
+		let v = DefaultComponent(T::from_world(world));
+		world.insert_resource(v);
+	}
+	let default_res_component_id = world.components().get_resource_id(std::any::TypeId::of::<DefaultComponent<T>>()).unwrap();
+	let default_res_archetype_component_id = world.storages().resources.get(default_res_component_id).unwrap().id();
+
+	OrDefaultState {
+		component_state: world.init_component::<T>(),
+		default_res_component_id: default_res_component_id,
+		default_res_archetype_component_id: default_res_archetype_component_id,
+	}
+}
+
+/// 变化过滤器，与OrDefault<T>配合使用
+/// 实体存在T时，等同于Changed<T>；
+/// 实体不存在T时（即OrDefault<T>取到的是默认值），如果单例DefaultComponent<T>在上次运行后被修改，则认为该实体发生了变化
+pub struct ChangedOrDefault<T> {
+	mark: PhantomData<T>,
+}
+
+unsafe impl<T: Component + FromWorld> ReadOnlyWorldQuery for ChangedOrDefault<T> {}
+
+unsafe impl<T: Component + FromWorld> WorldQuery for ChangedOrDefault<T> {
+    type Fetch<'w> = ChangedOrDefaultFetch<'w, T>;
+    type Item<'w> = bool;
+    type ReadOnly = Self;
+    type State = OrDefaultState;
+
+    fn shrink<'wlong: 'wshort, 'wshort>(item: bool) -> bool {
+        item
+    }
+
+    const IS_DENSE: bool = <Changed<T> as WorldQuery>::IS_DENSE;
+
+    const IS_ARCHETYPAL: bool = false;
+
+    unsafe fn init_fetch<'w>(
+        world: UnsafeWorldCell<'w>,
+        state: &Self::State,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> ChangedOrDefaultFetch<'w, T> {
+		let default_changed = match world.storages().resources.get(state.default_res_component_id).and_then(|r| r.get_ticks()) {
+			Some(ticks) => ticks.is_changed(last_run, this_run),
+			None => false,
+		};
+        ChangedOrDefaultFetch {
+            inner: <Changed<T>>::init_fetch(world, &state.component_state, last_run, this_run),
+			default_changed,
+			matches: false,
+        }
+    }
+
+    unsafe fn clone_fetch<'w>(fetch: &Self::Fetch<'w>) -> Self::Fetch<'w> {
+        ChangedOrDefaultFetch {
+            inner: <Changed<T>>::clone_fetch(&fetch.inner),
+            default_changed: fetch.default_changed,
+			matches: fetch.matches,
+        }
+    }
+
+    #[inline]
+    unsafe fn set_archetype<'w>(
+        fetch: &mut Self::Fetch<'w>,
+        state: &Self::State,
+        archetype: &'w Archetype,
+        table: &'w Table,
+    ) {
+		fetch.matches = archetype.contains(state.component_state);
+		if fetch.matches {
+			<Changed<T>>::set_archetype(&mut fetch.inner, &state.component_state, archetype, table)
+		}
+    }
+
+    #[inline]
+    unsafe fn set_table<'w>(
+        fetch: &mut Self::Fetch<'w>,
+        state: &Self::State,
+        table: &'w Table,
+    ) {
+		fetch.matches = table.has_column(state.component_state);
+		if fetch.matches {
+			<Changed<T>>::set_table(&mut fetch.inner, &state.component_state, table);
+		}
+    }
+
+    #[inline(always)]
+    unsafe fn fetch<'w>(
+        fetch: &mut Self::Fetch<'w>,
+        entity: Entity,
+        table_row: TableRow,
+    ) -> Self::Item<'w> {
+		if fetch.matches {
+			<Changed<T>>::fetch(&mut fetch.inner, entity, table_row)
+		} else {
+			fetch.default_changed
+		}
+    }
+
+    #[inline(always)]
+    unsafe fn filter_fetch(
+        fetch: &mut Self::Fetch<'_>,
+        entity: Entity,
+        table_row: TableRow,
+    ) -> bool {
+        Self::fetch(fetch, entity, table_row)
+    }
+
+    fn update_component_access(state: &Self::State, access: &mut FilteredAccess<ComponentId>) {
+        <Changed<T>>::update_component_access(&state.component_state, access);
+		assert!(
+            !access.access().has_write(state.default_res_component_id),
+            "ChangedOrDefault<{}> conflicts with a previous access in this query. Shared access cannot coincide with exclusive access.",
+                std::any::type_name::<T>(),
+        );
+        access.add_read(state.default_res_component_id);
+    }
+
+    fn update_archetype_component_access(
+        state: &Self::State,
+        archetype: &Archetype,
+        access: &mut Access<ArchetypeComponentId>,
+    ) {
+		<Changed<T>>::update_archetype_component_access(&state.component_state, archetype, access);
+		access.add_read(state.default_res_archetype_component_id);
+	}
 
+    fn init_state(world: &mut World) -> OrDefaultState {
+		init_or_default_state::<T>(world)
+    }
+
+    fn matches_component_set(
+        _state: &OrDefaultState,
+        _set_contains_id: &impl Fn(ComponentId) -> bool,
+    ) -> bool {
+		true
+    }
+}
+
+#[doc(hidden)]
+pub struct ChangedOrDefaultFetch<'w, T: Component> {
+	inner: <Changed<T> as WorldQuery>::Fetch<'w>,
+	default_changed: bool,
+	matches: bool,
+}
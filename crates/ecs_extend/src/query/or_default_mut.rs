@@ -0,0 +1,110 @@
+//! OrDefaultMut, 可写的OrDefault， 组件不存在时， 首次写入会以默认值的拷贝插入组件（写时复制）
+
+use bevy_ecs::{
+	archetype::Archetype,
+	component::Tick,
+	prelude::{Component, Entity, World},
+	system::{Query, Res, SystemMeta, SystemParam},
+	world::{FromWorld, unsafe_world_cell::UnsafeWorldCell},
+};
+use pi_hash::XHashMap;
+
+use super::or_default::DefaultComponent;
+
+/// 可写的OrDefault
+/// 读取时， 组件存在则返回组件， 否则返回单例DefaultComponent<T>的值
+/// 写入时， 组件存在则直接修改组件， 否则克隆DefaultComponent<T>的值， 修改后记录在待插入的组件中， 在apply_deferred时插入到实体上
+/// apply_deferred之前， 对同一个实体的多次写入会在上次写入的结果上继续修改， 每个实体只插入一次（最后一次写入的结果）
+pub struct OrDefaultMut<'w, 's, T: Component + Clone + FromWorld> {
+	query: Query<'w, 's, &'static mut T>,
+	default_value: Res<'w, DefaultComponent<T>>,
+	// 待插入（尚未生效）的组件
+	pending: &'s mut XHashMap<Entity, T>,
+}
+
+unsafe impl<T: Component + Clone + FromWorld> SystemParam for OrDefaultMut<'_, '_, T> {
+	type State = (
+		<Query<'static, 'static, &'static mut T> as SystemParam>::State,
+		<Res<'static, DefaultComponent<T>> as SystemParam>::State,
+		XHashMap<Entity, T>,
+	);
+	type Item<'world, 'state> = OrDefaultMut<'world, 'state, T>;
+
+	fn init_state(world: &mut World, system_meta: &mut SystemMeta) -> Self::State {
+		if let None = world.get_resource::<DefaultComponent<T>>() {
+			let v = DefaultComponent(T::from_world(world));
+			world.insert_resource(v);
+		}
+		(
+			<Query<'static, 'static, &'static mut T> as SystemParam>::init_state(world, system_meta),
+			<Res<'static, DefaultComponent<T>> as SystemParam>::init_state(world, system_meta),
+			XHashMap::default(),
+		)
+	}
+
+	fn new_archetype(state: &mut Self::State, archetype: &Archetype, system_meta: &mut SystemMeta) {
+		<Query<'static, 'static, &'static mut T> as SystemParam>::new_archetype(&mut state.0, archetype, system_meta);
+	}
+
+	fn apply(state: &mut Self::State, _system_meta: &SystemMeta, world: &mut World) {
+		for (entity, value) in state.2.drain() {
+			if let Some(mut r) = world.get_entity_mut(entity) {
+				r.insert(value);
+			}
+		}
+	}
+
+	#[inline]
+	unsafe fn get_param<'w, 's>(
+		state: &'s mut Self::State,
+		system_meta: &SystemMeta,
+		world: UnsafeWorldCell<'w>,
+		change_tick: Tick,
+	) -> Self::Item<'w, 's> {
+		let (query, default_value, pending) = state;
+		OrDefaultMut {
+			query: <Query<'w, 's, &'static mut T> as SystemParam>::get_param(query, system_meta, world, change_tick),
+			default_value: <Res<'w, DefaultComponent<T>> as SystemParam>::get_param(default_value, system_meta, world, change_tick),
+			pending,
+		}
+	}
+}
+
+impl<'w, 's, T: Component + Clone + FromWorld> OrDefaultMut<'w, 's, T> {
+	/// 取到实体上的组件， 不存在时返回默认值
+	pub fn get(&self, entity: Entity) -> &T {
+		if let Some(r) = self.pending.get(&entity) {
+			return r;
+		}
+		match self.query.get(entity) {
+			Ok(r) => r,
+			_ => &self.default_value.0,
+		}
+	}
+
+	/// 实体上是否已经存在该组件（包含本次运行中写入、尚未生效的组件）
+	pub fn contains(&self, entity: Entity) -> bool {
+		self.pending.contains_key(&entity) || self.query.contains(entity)
+	}
+
+	/// 修改实体上的组件
+	/// 组件存在时直接修改； 不存在时， 在默认值的拷贝上修改， 并将修改结果插入到实体上
+	pub fn write<R>(&mut self, entity: Entity, f: impl FnOnce(&mut T) -> R) -> R {
+		if let Ok(mut r) = self.query.get_mut(entity) {
+			return f(&mut *r);
+		}
+
+		let value = self.pending.entry(entity).or_insert_with(|| self.default_value.0.clone());
+		f(value)
+	}
+
+	/// 为实体设置组件
+	pub fn set(&mut self, entity: Entity, value: T) {
+		self.write(entity, move |r| *r = value);
+	}
+
+	/// 默认值
+	pub fn default_value(&self) -> &T {
+		&self.default_value.0
+	}
+}
@@ -0,0 +1,95 @@
+//! 无窗口的外壳，用于在测试中驱动插件
+//! 不依赖winit等平台窗口，帧推由调用者逐帧确定性地推进
+
+use bevy_app::{App, Plugins};
+use bevy_ecs::prelude::World;
+
+use crate::TShell;
+
+/// 无窗口外壳
+pub struct HeadlessShell {
+	app: App,
+	frame: u64,
+	is_ready: bool,
+}
+
+impl Default for HeadlessShell {
+	fn default() -> Self {
+		Self::new(App::new())
+	}
+}
+
+impl HeadlessShell {
+	/// 以指定的App创建外壳
+	pub fn new(app: App) -> Self {
+		Self {
+			app,
+			frame: 0,
+			is_ready: false,
+		}
+	}
+
+	/// 添加插件， 必须在第一次帧推之前调用
+	pub fn add_plugins<M>(&mut self, plugins: impl Plugins<M>) -> &mut Self {
+		assert!(!self.is_ready, "HeadlessShell: plugins must be added before the first step");
+		self.app.add_plugins(plugins);
+		self
+	}
+
+	/// 已推进的帧数
+	pub fn frame(&self) -> u64 {
+		self.frame
+	}
+
+	/// 推进n帧
+	pub fn step(&mut self, n: usize) -> &mut Self {
+		self.ready();
+		for _ in 0..n {
+			self.app.update();
+			self.frame += 1;
+		}
+		self
+	}
+
+	/// 逐帧推进，直到条件满足，或已推进max帧
+	/// 返回条件是否满足
+	pub fn step_until(&mut self, max: usize, mut cond: impl FnMut(&World) -> bool) -> bool {
+		self.ready();
+		for _ in 0..max {
+			if cond(&self.app.world) {
+				return true;
+			}
+			self.app.update();
+			self.frame += 1;
+		}
+		cond(&self.app.world)
+	}
+
+	// 完成插件的构建（与App::run中的流程一致）
+	fn ready(&mut self) {
+		if self.is_ready {
+			return;
+		}
+		self.is_ready = true;
+		self.app.finish();
+		self.app.cleanup();
+	}
+}
+
+impl TShell for HeadlessShell {
+	fn world(&self) -> &World {
+		&self.app.world
+	}
+
+	fn world_mut(&mut self) -> &mut World {
+		&mut self.app.world
+	}
+
+	fn app(&self) -> &App {
+		&self.app
+	}
+
+	fn app_mut(&mut self) -> &mut App {
+		&mut self.app
+	}
+}
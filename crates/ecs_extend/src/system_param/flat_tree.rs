@@ -0,0 +1,201 @@
+//! 扁平树缓存
+//! 以先序排列的数组缓存实体树（实体、层次、子树大小），使子树的递归迭代变为连续内存的遍历
+//! 缓存根据Layer修改事件，以根为单位增量更新
+
+use std::slice::Iter;
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_ecs::{
+	prelude::{Entity, EventReader, Resource},
+	query::Changed,
+	system::ResMut,
+};
+use pi_map::vecmap::VecMap;
+use pi_null::Null;
+
+use super::{
+	layer_dirty::ComponentEvent,
+	tree::{EntityTree, Layer, TreeKey},
+};
+
+/// 扁平树缓存插件， 添加单例`FlatTree`， 并在PostUpdate阶段根据树的修改更新缓存
+/// 如需在同一帧内使用最新的缓存， 可自行将`update_flat_tree`安排在修改树的system之后
+pub struct FlatTreePlugin;
+
+impl Plugin for FlatTreePlugin {
+	fn build(&self, app: &mut App) {
+		if !app.world.contains_resource::<bevy_ecs::event::Events<ComponentEvent<Changed<Layer>>>>() {
+			app.add_event::<ComponentEvent<Changed<Layer>>>();
+		}
+		app.init_resource::<FlatTree>()
+			.add_systems(PostUpdate, update_flat_tree);
+	}
+}
+
+/// 先序排列的扁平树
+/// 每棵树（以根划分）在数组中占据一段连续的区间， 节点的所有递归子节点紧跟在节点之后
+#[derive(Debug, Default, Resource)]
+pub struct FlatTree {
+	entities: Vec<Entity>,
+	depths: Vec<usize>,
+	// 以该节点为根的子树大小（包含自身）
+	sizes: Vec<usize>,
+	// 节点所在树的根
+	roots: Vec<Entity>,
+	// 实体索引 -> 在数组中的位置
+	index: VecMap<usize>,
+}
+
+impl FlatTree {
+	/// 缓存的节点数量
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.entities.len()
+	}
+
+	/// 先序排列的实体
+	#[inline]
+	pub fn entities(&self) -> &[Entity] {
+		&self.entities
+	}
+
+	/// 先序排列的层次
+	#[inline]
+	pub fn depths(&self) -> &[usize] {
+		&self.depths
+	}
+
+	/// 先序排列的子树大小（包含自身）
+	#[inline]
+	pub fn sizes(&self) -> &[usize] {
+		&self.sizes
+	}
+
+	/// 实体在数组中的位置
+	pub fn position(&self, entity: Entity) -> Option<usize> {
+		match self.index.get(entity.index() as usize) {
+			Some(pos) if self.entities[*pos] == entity => Some(*pos),
+			_ => None,
+		}
+	}
+
+	/// 以entity为根的子树（包含entity自身）
+	pub fn subtree(&self, entity: Entity) -> Option<&[Entity]> {
+		self.position(entity).map(|pos| &self.entities[pos..pos + self.sizes[pos]])
+	}
+
+	/// entity的所有递归子节点（先序）
+	pub fn descendants(&self, entity: Entity) -> Option<&[Entity]> {
+		self.position(entity).map(|pos| &self.entities[pos + 1..pos + self.sizes[pos]])
+	}
+
+	/// 迭代指定节点的所有递归子节点，节点不在缓存中时，返回空迭代器
+	pub fn recursive_iter(&self, entity: Entity) -> Iter<Entity> {
+		self.descendants(entity).unwrap_or(&[]).iter()
+	}
+
+	/// 以根为单位， 根据树的当前状态， 重建受影响的树
+	pub fn update(&mut self, tree: &EntityTree, changed: impl Iterator<Item = Entity>) {
+		let mut affected: Vec<Entity> = Vec::new();
+		for entity in changed {
+			// 原来所在的树
+			if let Some(pos) = self.position(entity) {
+				let root = self.roots[pos];
+				if !affected.contains(&root) {
+					affected.push(root);
+				}
+			}
+			// 现在所在的树
+			if let Some(layer) = tree.get_layer(entity) {
+				if !layer.layer().is_null() {
+					let root = layer.root();
+					if !TreeKey(root).is_null() && !affected.contains(&root) {
+						affected.push(root);
+					}
+				}
+			}
+		}
+		if affected.len() == 0 {
+			return;
+		}
+
+		// 找到受影响的树在数组中的原区间， 并移除原区间中实体的索引
+		let mut ranges = Vec::with_capacity(affected.len());
+		for root in affected.into_iter() {
+			let range = match self.position(root) {
+				Some(pos) if self.roots[pos] == root => Some((pos, pos + self.sizes[pos])),
+				_ => None,
+			};
+			if let Some((start, end)) = range {
+				for e in self.entities[start..end].iter() {
+					self.index.remove(e.index() as usize);
+				}
+			}
+			ranges.push((root, range));
+		}
+
+		// 从后向前替换， 保证前面区间的位置不变； 原来不在缓存中的树追加到末尾
+		ranges.sort_by(|a, b| match (a.1, b.1) {
+			(Some(a), Some(b)) => b.0.cmp(&a.0),
+			(Some(_), None) => std::cmp::Ordering::Less,
+			(None, Some(_)) => std::cmp::Ordering::Greater,
+			(None, None) => std::cmp::Ordering::Equal,
+		});
+		let mut reindex_start = self.entities.len();
+		let mut flat = FlatTree::default();
+		for (root, range) in ranges.into_iter() {
+			flat.entities.clear();
+			flat.depths.clear();
+			flat.sizes.clear();
+			flat.roots.clear();
+			if let Some(layer) = tree.get_layer(root) {
+				if !layer.layer().is_null() && layer.root() == root {
+					flat.push_subtree(tree, root, root, layer.layer());
+				}
+			}
+
+			let (start, end) = range.unwrap_or((self.entities.len(), self.entities.len()));
+			self.entities.splice(start..end, flat.entities.drain(..));
+			self.depths.splice(start..end, flat.depths.drain(..));
+			self.sizes.splice(start..end, flat.sizes.drain(..));
+			self.roots.splice(start..end, flat.roots.drain(..));
+			reindex_start = reindex_start.min(start);
+		}
+
+		for pos in reindex_start..self.entities.len() {
+			self.index.insert(self.entities[pos].index() as usize, pos);
+		}
+	}
+
+	fn push_subtree(&mut self, tree: &EntityTree, root: Entity, node: Entity, depth: usize) {
+		let pos = self.entities.len();
+		self.entities.push(node);
+		self.depths.push(depth);
+		self.sizes.push(1);
+		self.roots.push(root);
+
+		if let Some(down) = tree.get_down(node) {
+			let mut child = down.head();
+			while !TreeKey(child).is_null() {
+				self.push_subtree(tree, root, child, depth + 1);
+				child = match tree.get_up(child) {
+					Some(up) => up.next(),
+					None => break,
+				};
+			}
+		}
+		self.sizes[pos] = self.entities.len() - pos;
+	}
+}
+
+/// 根据Layer修改事件更新扁平树缓存
+pub fn update_flat_tree(
+	mut flat: ResMut<FlatTree>,
+	tree: EntityTree,
+	mut events: EventReader<ComponentEvent<Changed<Layer>>>,
+) {
+	if events.is_empty() {
+		return;
+	}
+	flat.update(&tree, events.iter().map(|r| r.id));
+}
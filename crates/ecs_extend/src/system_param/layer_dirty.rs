@@ -1,6 +1,6 @@
 //! 层脏
 
-use super::tree::{EntityTree, RecursiveIterator};
+use super::{tree::{EntityTree, RecursiveIterator}, flat_tree::FlatTree};
 use bevy_ecs::{
 	prelude::{World, Event},
     event::ManualEventReader,
@@ -127,6 +127,19 @@ impl<'w, 's, F: Dirty> LayerDirty<'w, 's, F>
         }
     }
 
+    /// 与iter相同，但脏节点的递归子节点从扁平树缓存中连续迭代
+    /// 缓存中不存在该节点、或缓存的子树大小与实际不符（缓存尚未更新）时，回退为通过EntityTree迭代
+    pub fn iter_flat<'a>(&'a mut self, flat: &'a FlatTree) -> FlatLayerDirtyIter<'w, 's, 'a> {
+        self.init();
+        FlatLayerDirtyIter {
+            iter_inner: self.layer_list.iter(),
+            mark_inner: &mut self.dirty_mark,
+            tree: &self.entity_tree,
+            flat,
+            pre_iter: None,
+        }
+    }
+
     /// 返回一个手动迭代器
     pub fn iter_manual<'a>(&'a mut self) -> ManualLayerDirtyIter<'w, 's, 'a> {
         self.init();
@@ -472,6 +485,72 @@ impl<'w, 's, 'a> Iterator for AutoLayerDirtyIter<'w, 's, 'a> {
     }
 }
 
+enum SubTreeIter<'w, 's, 'a> {
+    Flat(Iter<'a, Entity>),
+    Tree(RecursiveIterator<'a, EntityTree<'w, 's>>),
+}
+
+/// 使用扁平树缓存迭代子树的自动迭代器
+pub struct FlatLayerDirtyIter<'w, 's, 'a> {
+    iter_inner: DirtyIterator<'a, Entity>,
+
+    mark_inner: &'a mut DirtyMark,
+
+    tree: &'a EntityTree<'w, 's>,
+    flat: &'a FlatTree,
+    pre_iter: Option<SubTreeIter<'w, 's, 'a>>,
+}
+
+impl<'w, 's, 'a> Iterator for FlatLayerDirtyIter<'w, 's, 'a> {
+    type Item = Entity;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(r) = &mut self.pre_iter {
+            // 上次迭代的脏还没完成，继续迭代
+            let next = match r {
+                SubTreeIter::Flat(r) => r.next().cloned(),
+                SubTreeIter::Tree(r) => r.next(),
+            };
+            match next {
+                Some(next) => {
+                    self.mark_inner.remove(&next); // 标记为不脏
+                    return Some(next);
+                }
+                None => self.pre_iter = None,
+            };
+        }
+
+        // 上一个子树迭代完成，继续迭代下一个脏
+        loop {
+            let item = self.iter_inner.next();
+            if let Some((local, layer)) = item {
+                if let Some(layer1) = self.mark_inner.get(local) {
+                    let layer1 = *layer1;
+                    self.mark_inner.remove(local); // 标记为不脏
+
+                    // 记录的层次和实际层次相等，并且在idtree中的层次也相等，则返回该值
+                    if layer == layer1 {
+                        if let Some(r) = self.tree.get_layer(*local) {
+                            if r.layer() == layer {
+                                if let Some(down) = self.tree.get_down(*local) {
+                                    self.pre_iter = Some(match self.flat.subtree(*local) {
+                                        Some(subtree) if subtree.len() == down.count() => SubTreeIter::Flat(subtree[1..].iter()),
+                                        _ => SubTreeIter::Tree(self.tree.recursive_iter(down.head())),
+                                    });
+                                }
+                                return Some(*local);
+                            }
+                        }
+                    }
+                }
+            } else {
+                return None;
+            }
+        }
+    }
+}
+
 // #[derive(SystemParam)]
 // pub struct OrSystemParam<T> {
 
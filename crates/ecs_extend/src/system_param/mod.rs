@@ -1,3 +1,4 @@
 pub mod tree;
 pub mod layer_dirty;
+pub mod flat_tree;
 pub mod res;
\ No newline at end of file
@@ -389,6 +389,7 @@ impl<'w, 's> StorageMut<TreeKey> for TreeStorageMut<'w, 's> {
 	fn remove_layer(&mut self, k: TreeKey) {
 		if let Ok(mut write) = self.layer_query.get_mut(k.0) {
 			*write = Layer(Layer1::default());
+			self.layer_notify.send(ComponentEvent::new(k.0));
 		}
 	}
 
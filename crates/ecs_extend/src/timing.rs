@@ -0,0 +1,390 @@
+//! system耗时统计
+//! 添加TimingPlugin后，主调度（Main）中运行的每个调度（First、Update、Last等）都会统计耗时，覆盖其中的所有system；
+//! 需要细分到单个system时，通过add_timed_system添加，每次运行都会统计cpu耗时（bevy的执行器不提供逐system的钩子，只能按需包装）；
+//! 每帧结束时按调度、system和系统集汇总，保留最近window帧的数据，计算最小、平均、最大耗时
+
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy_app::{App, Last, Main, MainScheduleOrder, Plugin, PostStartup, PreStartup, Startup};
+use bevy_ecs::{
+	archetype::ArchetypeComponentId,
+	component::{ComponentId, Tick},
+	prelude::{Resource, World},
+	query::Access,
+	schedule::{BoxedScheduleLabel, IntoSystemConfigs, Schedule, ScheduleLabel, SystemSet},
+	system::{IntoSystem, Local, Res, ResMut, System},
+	world::{unsafe_world_cell::UnsafeWorldCell, Mut},
+};
+use flume::{Receiver, Sender};
+use pi_hash::XHashMap;
+use pi_time::Instant;
+use serde::Serialize;
+
+/// 耗时统计插件
+pub struct TimingPlugin {
+	/// 统计窗口（帧数）
+	pub window: usize,
+}
+
+impl Default for TimingPlugin {
+	fn default() -> Self {
+		Self { window: 120 }
+	}
+}
+
+impl Plugin for TimingPlugin {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(SystemTimings::new(self.window))
+			.init_resource::<TimingCollector>()
+			.add_systems(Last, collect_timings);
+
+		// 以统计耗时的主调度替换Main（主调度已被其他插件替换时，不统计调度耗时）
+		if app.main_schedule_label == (Box::new(Main) as BoxedScheduleLabel) {
+			let mut schedule = Schedule::new();
+			schedule.add_systems(run_timed_main);
+			app.add_schedule(TimedMain, schedule);
+			app.main_schedule_label = Box::new(TimedMain);
+		} else {
+			log::warn!("main schedule is replaced, schedule timings are disabled: {:?}", app.main_schedule_label);
+		}
+	}
+}
+
+/// 统计耗时的主调度，与Main相同，依次运行MainScheduleOrder中的调度，并统计每个调度的耗时
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ScheduleLabel)]
+pub struct TimedMain;
+
+/// 与Main::run_main相同，额外统计每个调度的耗时
+pub fn run_timed_main(world: &mut World, mut run_at_least_once: Local<bool>) {
+	if !*run_at_least_once {
+		let _ = world.try_run_schedule(PreStartup);
+		let _ = world.try_run_schedule(Startup);
+		let _ = world.try_run_schedule(PostStartup);
+		*run_at_least_once = true;
+	}
+
+	let mut times = Vec::new();
+	world.resource_scope(|world, order: Mut<MainScheduleOrder>| {
+		for label in &order.labels {
+			let t = Instant::now();
+			if world.try_run_schedule(&**label).is_ok() {
+				times.push((format!("{:?}", label), Instant::now() - t));
+			}
+		}
+	});
+
+	if let Some(mut timings) = world.get_resource_mut::<SystemTimings>() {
+		let window = timings.window;
+		for (name, time) in times.into_iter() {
+			timings.schedules.entry(name.into()).or_default().push(time, window);
+		}
+	}
+}
+
+/// 一个system或系统集在统计窗口内的耗时
+#[derive(Debug, Default, Clone)]
+pub struct TimingStat {
+	samples: VecDeque<Duration>,
+}
+
+impl TimingStat {
+	fn push(&mut self, time: Duration, window: usize) {
+		if self.samples.len() >= window.max(1) {
+			self.samples.pop_front();
+		}
+		self.samples.push_back(time);
+	}
+
+	/// 最近一帧的耗时
+	pub fn last(&self) -> Duration {
+		self.samples.back().cloned().unwrap_or_default()
+	}
+
+	pub fn min(&self) -> Duration {
+		self.samples.iter().min().cloned().unwrap_or_default()
+	}
+
+	pub fn max(&self) -> Duration {
+		self.samples.iter().max().cloned().unwrap_or_default()
+	}
+
+	pub fn avg(&self) -> Duration {
+		if self.samples.is_empty() {
+			return Duration::ZERO;
+		}
+		self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+	}
+
+	/// 窗口内的帧数
+	pub fn count(&self) -> usize {
+		self.samples.len()
+	}
+}
+
+/// 耗时统计结果
+#[derive(Debug, Resource)]
+pub struct SystemTimings {
+	window: usize,
+	schedules: XHashMap<Arc<str>, TimingStat>,
+	systems: XHashMap<Cow<'static, str>, TimingStat>,
+	sets: XHashMap<Arc<str>, TimingStat>,
+}
+
+impl SystemTimings {
+	pub fn new(window: usize) -> Self {
+		Self {
+			window,
+			schedules: XHashMap::default(),
+			systems: XHashMap::default(),
+			sets: XHashMap::default(),
+		}
+	}
+
+	pub fn window(&self) -> usize {
+		self.window
+	}
+
+	/// 调度的耗时，name为调度标签的Debug输出（如"Update"）
+	pub fn schedule(&self, name: &str) -> Option<&TimingStat> {
+		self.schedules.get(name)
+	}
+
+	pub fn system(&self, name: &str) -> Option<&TimingStat> {
+		self.systems.get(name)
+	}
+
+	pub fn set(&self, name: &str) -> Option<&TimingStat> {
+		self.sets.get(name)
+	}
+
+	pub fn schedules(&self) -> impl Iterator<Item = (&str, &TimingStat)> {
+		self.schedules.iter().map(|(k, v)| (k.as_ref(), v))
+	}
+
+	pub fn systems(&self) -> impl Iterator<Item = (&str, &TimingStat)> {
+		self.systems.iter().map(|(k, v)| (k.as_ref(), v))
+	}
+
+	pub fn sets(&self) -> impl Iterator<Item = (&str, &TimingStat)> {
+		self.sets.iter().map(|(k, v)| (k.as_ref(), v))
+	}
+
+	pub fn clear(&mut self) {
+		self.schedules.clear();
+		self.systems.clear();
+		self.sets.clear();
+	}
+
+	/// 统计报告，按平均耗时从大到小排列
+	pub fn report(&self) -> TimingReport {
+		TimingReport {
+			window: self.window,
+			schedules: summarize(self.schedules()),
+			systems: summarize(self.systems()),
+			sets: summarize(self.sets()),
+		}
+	}
+
+	pub fn to_json(&self) -> serde_json::Result<String> {
+		serde_json::to_string(&self.report())
+	}
+}
+
+/// 耗时汇总（单位：微秒）
+#[derive(Debug, Clone, Serialize)]
+pub struct TimingSummary {
+	pub name: String,
+	pub count: usize,
+	pub last: u64,
+	pub min: u64,
+	pub avg: u64,
+	pub max: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimingReport {
+	pub window: usize,
+	pub schedules: Vec<TimingSummary>,
+	pub systems: Vec<TimingSummary>,
+	pub sets: Vec<TimingSummary>,
+}
+
+fn summarize<'a>(iter: impl Iterator<Item = (&'a str, &'a TimingStat)>) -> Vec<TimingSummary> {
+	let mut list: Vec<TimingSummary> = iter
+		.map(|(name, stat)| TimingSummary {
+			name: name.to_string(),
+			count: stat.count(),
+			last: stat.last().as_micros() as u64,
+			min: stat.min().as_micros() as u64,
+			avg: stat.avg().as_micros() as u64,
+			max: stat.max().as_micros() as u64,
+		})
+		.collect();
+	list.sort_by(|a, b| b.avg.cmp(&a.avg));
+	list
+}
+
+struct TimingRecord {
+	system: Cow<'static, str>,
+	set: Option<Arc<str>>,
+	time: Duration,
+}
+
+/// system运行时，通过通道提交耗时，避免并行的system争用统计结果
+#[derive(Resource)]
+pub struct TimingCollector {
+	sender: Sender<TimingRecord>,
+	receiver: Receiver<TimingRecord>,
+}
+
+impl Default for TimingCollector {
+	fn default() -> Self {
+		let (sender, receiver) = flume::unbounded();
+		Self { sender, receiver }
+	}
+}
+
+/// 汇总本帧的耗时
+pub fn collect_timings(mut timings: ResMut<SystemTimings>, collector: Res<TimingCollector>) {
+	let mut systems: XHashMap<Cow<'static, str>, Duration> = XHashMap::default();
+	let mut sets: XHashMap<Arc<str>, Duration> = XHashMap::default();
+	// 同一system在一帧内可能运行多次，累加
+	for r in collector.receiver.try_iter() {
+		if let Some(set) = r.set {
+			*sets.entry(set).or_default() += r.time;
+		}
+		*systems.entry(r.system).or_default() += r.time;
+	}
+
+	let window = timings.window;
+	for (name, time) in systems.into_iter() {
+		timings.systems.entry(name).or_default().push(time, window);
+	}
+	for (name, time) in sets.into_iter() {
+		timings.sets.entry(name).or_default().push(time, window);
+	}
+}
+
+/// 统计耗时的system
+pub struct TimedSystem<S: System<In = (), Out = ()>> {
+	system: S,
+	set: Option<Arc<str>>,
+	// 未添加TimingPlugin时为None，不统计
+	sender: Option<Sender<TimingRecord>>,
+}
+
+impl<S: System<In = (), Out = ()>> TimedSystem<S> {
+	pub fn new(system: S) -> Self {
+		Self { system, set: None, sender: None }
+	}
+
+	/// 耗时同时累计到系统集上
+	pub fn with_set(mut self, set: impl SystemSet) -> Self {
+		self.set = Some(format!("{:?}", set).into());
+		self
+	}
+
+	fn record(&self, time: Duration) {
+		if let Some(sender) = &self.sender {
+			let _ = sender.send(TimingRecord {
+				system: self.system.name(),
+				set: self.set.clone(),
+				time,
+			});
+		}
+	}
+}
+
+impl<S: System<In = (), Out = ()>> System for TimedSystem<S> {
+	type In = ();
+	type Out = ();
+
+	fn name(&self) -> Cow<'static, str> {
+		self.system.name()
+	}
+
+	fn type_id(&self) -> TypeId {
+		TypeId::of::<Self>()
+	}
+
+	fn component_access(&self) -> &Access<ComponentId> {
+		self.system.component_access()
+	}
+
+	fn archetype_component_access(&self) -> &Access<ArchetypeComponentId> {
+		self.system.archetype_component_access()
+	}
+
+	fn is_send(&self) -> bool {
+		self.system.is_send()
+	}
+
+	fn is_exclusive(&self) -> bool {
+		self.system.is_exclusive()
+	}
+
+	unsafe fn run_unsafe(&mut self, input: Self::In, world: UnsafeWorldCell) -> Self::Out {
+		let t = Instant::now();
+		self.system.run_unsafe(input, world);
+		self.record(Instant::now() - t);
+	}
+
+	fn run(&mut self, input: Self::In, world: &mut World) -> Self::Out {
+		let t = Instant::now();
+		self.system.run(input, world);
+		self.record(Instant::now() - t);
+	}
+
+	fn apply_deferred(&mut self, world: &mut World) {
+		self.system.apply_deferred(world);
+	}
+
+	fn initialize(&mut self, world: &mut World) {
+		self.sender = world.get_resource::<TimingCollector>().map(|r| r.sender.clone());
+		self.system.initialize(world);
+	}
+
+	fn update_archetype_component_access(&mut self, world: UnsafeWorldCell) {
+		self.system.update_archetype_component_access(world);
+	}
+
+	fn check_change_tick(&mut self, change_tick: Tick) {
+		self.system.check_change_tick(change_tick);
+	}
+
+	fn default_system_sets(&self) -> Vec<Box<dyn SystemSet>> {
+		self.system.default_system_sets()
+	}
+
+	fn get_last_run(&self) -> Tick {
+		self.system.get_last_run()
+	}
+
+	fn set_last_run(&mut self, last_run: Tick) {
+		self.system.set_last_run(last_run);
+	}
+}
+
+/// 调度的耗时由TimingPlugin自动统计，需要细分到单个system时，通过以下方法添加
+pub trait AddTimedSystem {
+	/// 添加统计耗时的system
+	fn add_timed_system<M>(&mut self, schedule: impl ScheduleLabel, system: impl IntoSystem<(), (), M>) -> &mut Self;
+
+	/// 添加统计耗时的system，并放入系统集，耗时同时累计到该系统集上
+	fn add_timed_system_in_set<M>(&mut self, schedule: impl ScheduleLabel, set: impl SystemSet + Clone, system: impl IntoSystem<(), (), M>) -> &mut Self;
+}
+
+impl AddTimedSystem for App {
+	fn add_timed_system<M>(&mut self, schedule: impl ScheduleLabel, system: impl IntoSystem<(), (), M>) -> &mut Self {
+		self.add_systems(schedule, TimedSystem::new(IntoSystem::into_system(system)))
+	}
+
+	fn add_timed_system_in_set<M>(&mut self, schedule: impl ScheduleLabel, set: impl SystemSet + Clone, system: impl IntoSystem<(), (), M>) -> &mut Self {
+		let system = TimedSystem::new(IntoSystem::into_system(system)).with_set(set.clone());
+		self.add_systems(schedule, system.in_set(set))
+	}
+}
@@ -0,0 +1,34 @@
+//! 操作的应用顺序
+
+use bevy_app::App;
+use pi_bevy_ecs_extend::action::{Action, ActionList, ActionLog, AddAction};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Move(i32);
+
+impl Action for Move {}
+
+#[derive(Serialize, Deserialize)]
+struct Select(i32);
+
+impl Action for Select {
+	const PRIORITY: i32 = -1;
+}
+
+#[test]
+fn apply_by_priority() {
+	let mut app = App::new();
+	app.add_replayable_action::<Move>("move")
+		.add_replayable_action::<Select>("select");
+	app.world.resource_mut::<ActionLog>().start();
+
+	app.world.resource_mut::<ActionList<Move>>().push(Move(1));
+	app.world.resource_mut::<ActionList<Select>>().push(Select(2));
+	app.update();
+
+	// Select的优先级数值更小，虽然后注册，也先应用
+	let log = app.world.resource::<ActionLog>();
+	let types: Vec<&str> = log.frames[0].actions.iter().map(|r| r.ty.as_str()).collect();
+	assert_eq!(types, vec!["select", "move"]);
+}
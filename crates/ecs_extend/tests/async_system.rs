@@ -0,0 +1,32 @@
+//! 异步system
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use bevy_app::{App, Update};
+use bevy_ecs::prelude::{Resource, World};
+use pi_bevy_ecs_extend::async_system::AddAsyncSystem;
+
+#[derive(Resource)]
+struct Done;
+
+#[test]
+fn run_without_spawner() {
+	let mut app = App::new();
+	// 没有添加AsyncTasksPlugin，也没有插入AsyncSpawner
+	app.add_async_system(Update, || async {
+		Box::new(|world: &mut World| world.insert_resource(Done)) as Box<dyn FnOnce(&mut World) + Send>
+	});
+	app.finish();
+	app.cleanup();
+
+	// 结果在之后某一帧的同步点应用
+	for _ in 0..100 {
+		app.update();
+		if app.world.contains_resource::<Done>() {
+			return;
+		}
+		sleep(Duration::from_millis(10));
+	}
+	panic!("async system result is not applied");
+}
@@ -0,0 +1,96 @@
+//! 派发器按阶段运行system
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake};
+use std::thread::{self, Thread};
+
+use bevy_ecs::prelude::{Commands, Component, NonSend, Query, ResMut, Resource, World};
+use pi_bevy_ecs_extend::dispatch::interface::{
+	BuildErr, DispatchWorld, Dispatcher, SingleDispatcher, StageBuilder,
+};
+use pi_share::Share;
+
+#[derive(Component)]
+struct Value(i32);
+
+#[derive(Resource, Default)]
+struct Log(Vec<&'static str>);
+
+struct NotSendData;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+	fn wake(self: Arc<Self>) {
+		self.0.unpark();
+	}
+}
+
+fn block_on<F: Future>(f: F) -> F::Output {
+	let mut f = pin!(f);
+	let waker = Arc::new(ThreadWaker(thread::current())).into();
+	let mut cx = Context::from_waker(&waker);
+	loop {
+		match f.as_mut().poll(&mut cx) {
+			Poll::Ready(r) => return r,
+			Poll::Pending => thread::park(),
+		}
+	}
+}
+
+fn spawn_value(mut commands: Commands, mut log: ResMut<Log>) {
+	commands.spawn(Value(1));
+	log.0.push("first");
+}
+
+fn second_writer(mut log: ResMut<Log>) {
+	log.0.push("second");
+}
+
+fn sum_values(query: Query<&Value>, mut log: ResMut<Log>) {
+	// 第一阶段的Commands在阶段结束时已应用
+	if query.iter().map(|v| v.0).sum::<i32>() == 1 {
+		log.0.push("sum");
+	}
+}
+
+#[test]
+fn two_stages() {
+	let mut world = World::new();
+	world.init_resource::<Log>();
+	let dispatch_world = DispatchWorld::default();
+
+	let mut first = StageBuilder::new(dispatch_world.clone());
+	first.add_system(&mut world, spawn_value);
+	first.add_system(&mut world, second_writer);
+	let first = first.build(&world).unwrap();
+
+	let mut second = StageBuilder::new(dispatch_world.clone());
+	second.add_system(&mut world, sum_values);
+	let second = second.build(&world).unwrap();
+
+	let rt = pi_async_rt::rt::AsyncRuntimeBuilder::default_multi_thread(Some("dispatch_test"), None, None, None);
+	let mut dispatcher = SingleDispatcher::new(rt, dispatch_world);
+	dispatcher.init(vec![Share::new(first), Share::new(second)]);
+
+	block_on(dispatcher.run(&mut world));
+	// 同一资源的多个写system按加入顺序执行
+	assert_eq!(world.resource::<Log>().0, vec!["first", "second", "sum"]);
+	assert_eq!(dispatcher.statistics().len(), 3);
+}
+
+#[test]
+fn reject_exclusive_and_non_send() {
+	let mut world = World::new();
+	world.insert_non_send_resource(NotSendData);
+
+	let mut stage = StageBuilder::new(DispatchWorld::default());
+	stage.add_system(&mut world, |_world: &mut World| {});
+	assert!(matches!(stage.build(&world), Err(BuildErr::Exclusive(_))));
+
+	let mut stage = StageBuilder::new(DispatchWorld::default());
+	stage.add_system(&mut world, |_local: NonSend<NotSendData>| {});
+	assert!(matches!(stage.build(&world), Err(BuildErr::NotSend(_))));
+}
@@ -0,0 +1,69 @@
+//! 通过HeadlessShell驱动实体树
+
+use bevy_app::Update;
+use bevy_ecs::prelude::{Entity, Events, Resource, World};
+use bevy_ecs::query::Changed;
+use bevy_ecs::system::{Local, Res};
+use pi_bevy_ecs_extend::prelude::{Down, EntityTreeMut, Layer, Root, Up};
+use pi_bevy_ecs_extend::shell::HeadlessShell;
+use pi_bevy_ecs_extend::system_param::layer_dirty::ComponentEvent;
+use pi_bevy_ecs_extend::TShell;
+
+#[derive(Resource, Clone, Copy)]
+struct Nodes {
+	root: Entity,
+	child: Entity,
+}
+
+// 第一帧将root设为根节点，child设为root的子节点
+fn build_tree(nodes: Res<Nodes>, mut tree: EntityTreeMut, mut done: Local<bool>) {
+	if *done {
+		return;
+	}
+	*done = true;
+	tree.insert_child(nodes.root, Entity::from_bits(u64::MAX), 0);
+	tree.insert_child(nodes.child, nodes.root, 0);
+}
+
+fn new_shell() -> (HeadlessShell, Nodes) {
+	let mut shell = HeadlessShell::default();
+	shell
+		.app_mut()
+		.add_event::<ComponentEvent<Changed<Layer>>>()
+		.add_systems(Update, build_tree);
+	let nodes = spawn_nodes(shell.world_mut());
+	shell.world_mut().insert_resource(nodes);
+	(shell, nodes)
+}
+
+fn spawn_nodes(world: &mut World) -> Nodes {
+	let root = world.spawn((Up::default(), Down::default(), Layer::default())).id();
+	let child = world.spawn((Up::default(), Down::default(), Layer::default())).id();
+	Nodes { root, child }
+}
+
+#[test]
+fn headless_shell_builds_tree() {
+	let (mut shell, nodes) = new_shell();
+
+	shell.step(1);
+	assert_eq!(shell.frame(), 1);
+
+	let world = shell.world();
+	assert!(world.get::<Root>(nodes.root).is_some());
+	assert_eq!(world.get::<Up>(nodes.child).unwrap().parent(), nodes.root);
+	assert_eq!(world.get::<Down>(nodes.root).unwrap().head(), nodes.child);
+	let root_layer = world.get::<Layer>(nodes.root).unwrap().layer();
+	assert_eq!(world.get::<Layer>(nodes.child).unwrap().layer(), root_layer + 1);
+	assert_eq!(world.get::<Layer>(nodes.child).unwrap().root(), nodes.root);
+	// 层修改通过事件通知LayerDirty
+	assert!(!world.resource::<Events<ComponentEvent<Changed<Layer>>>>().is_empty());
+}
+
+#[test]
+fn headless_shell_step_until() {
+	let (mut shell, nodes) = new_shell();
+
+	assert!(shell.step_until(10, |world| world.get::<Root>(nodes.root).is_some()));
+	assert_eq!(shell.frame(), 1);
+}
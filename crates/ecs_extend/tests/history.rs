@@ -0,0 +1,66 @@
+//! 撤销、重做
+
+use bevy_app::App;
+use bevy_ecs::prelude::{Component, Entity};
+use pi_bevy_ecs_extend::action::ActionList;
+use pi_bevy_ecs_extend::history::{History, HistoryAction, HistoryPlugin, SetComponent};
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Value(i32);
+
+fn new_app() -> (App, Entity) {
+	let mut app = App::new();
+	app.add_plugins(HistoryPlugin::default());
+	let entity = app.world.spawn(Value(0)).id();
+	(app, entity)
+}
+
+fn run(app: &mut App, actions: Vec<HistoryAction>) {
+	let mut list = app.world.resource_mut::<ActionList<HistoryAction>>();
+	for r in actions {
+		list.push(r);
+	}
+	app.update();
+}
+
+fn value(app: &App, entity: Entity) -> i32 {
+	app.world.get::<Value>(entity).unwrap().0
+}
+
+#[test]
+fn undo_scope_after_implicit_actions() {
+	let (mut app, entity) = new_app();
+	run(&mut app, vec![
+		HistoryAction::Do(Box::new(SetComponent::new(entity, Value(1)))),
+		HistoryAction::Begin("scope".to_string()),
+		HistoryAction::Do(Box::new(SetComponent::new(entity, Value(2)))),
+		HistoryAction::End,
+	]);
+	assert_eq!(value(&app, entity), 2);
+	assert_eq!(app.world.resource::<History>().undo_list().len(), 2);
+
+	// 先撤销后应用的显式事务
+	run(&mut app, vec![HistoryAction::Undo]);
+	assert_eq!(value(&app, entity), 1);
+
+	run(&mut app, vec![HistoryAction::Undo]);
+	assert_eq!(value(&app, entity), 0);
+
+	run(&mut app, vec![HistoryAction::Redo, HistoryAction::Redo]);
+	assert_eq!(value(&app, entity), 2);
+}
+
+#[test]
+fn implicit_actions_after_scope() {
+	let (mut app, entity) = new_app();
+	run(&mut app, vec![
+		HistoryAction::Begin("scope".to_string()),
+		HistoryAction::Do(Box::new(SetComponent::new(entity, Value(1)))),
+		HistoryAction::End,
+		HistoryAction::Do(Box::new(SetComponent::new(entity, Value(2)))),
+	]);
+	run(&mut app, vec![HistoryAction::Undo]);
+	assert_eq!(value(&app, entity), 1);
+	run(&mut app, vec![HistoryAction::Undo]);
+	assert_eq!(value(&app, entity), 0);
+}
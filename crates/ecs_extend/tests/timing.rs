@@ -0,0 +1,29 @@
+//! 调度和system的耗时统计
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use bevy_app::{App, Update};
+use pi_bevy_ecs_extend::timing::{AddTimedSystem, SystemTimings, TimingPlugin};
+
+fn slow() {
+	sleep(Duration::from_millis(2));
+}
+
+#[test]
+fn time_schedules_and_systems() {
+	let mut app = App::new();
+	app.add_plugins(TimingPlugin::default())
+		// 未通过add_timed_system添加的system，耗时计入所在的调度
+		.add_systems(Update, slow)
+		.add_timed_system(Update, slow);
+	app.update();
+	app.update();
+
+	let timings = app.world.resource::<SystemTimings>();
+	let update = timings.schedule("Update").unwrap();
+	assert_eq!(update.count(), 2);
+	assert!(update.min() >= Duration::from_millis(4));
+	assert!(timings.schedule("First").is_some());
+	assert!(timings.systems().any(|(name, stat)| name.ends_with("slow") && stat.min() >= Duration::from_millis(2)));
+}
@@ -72,6 +72,7 @@ fn main() {
                         .add_plugins(PiAssetPlugin {
                             total_capacity: 256 * 1024 * 1024,
                             asset_config: AssetConfig::default(),
+                            ..Default::default()
                         })
                         .add_plugins(PiRenderPlugin::default());
                 }
@@ -1,4 +1,4 @@
-use crate::{node::Node, PiClearOptions, PiScreenTexture, RenderContext};
+use crate::{node::Node, PiClearOptions, PiRenderOutput, RenderContext};
 use bevy_ecs::{
     system::{Res, SystemState},
     world::World,
@@ -10,7 +10,7 @@ use pi_render::depend_graph::NodeId;
 use wgpu::StoreOp;
 
 /// 窗口清屏
-/// 注：此节点 只清屏窗口
+/// 注：此节点 只清屏渲染输出（窗口模式下为窗口，离屏模式下为离屏目标）
 pub(crate) struct ClearNode;
 
 pub const CLEAR_WIDNOW_NODE: &str = "clear_window";
@@ -19,7 +19,7 @@ impl Node for ClearNode {
     type Input = ();
     type Output = ();
     type BuildParam = ();
-	type RunParam = (Res<'static, PiScreenTexture>, Res<'static, PiClearOptions>);
+	type RunParam = (Res<'static, PiRenderOutput>, Res<'static, PiClearOptions>);
 
 	fn build<'a>(
 		&'a mut self,
@@ -48,9 +48,9 @@ impl Node for ClearNode {
 		_to: &'a [NodeId],
     ) -> BoxFuture<'a, Result<Self::Output, String>> {
         let (view, clear) = {
-            let (s, clear) = param.get(world);
+            let (output, clear) = param.get(world);
 
-            let view = s.0.as_ref().unwrap().view.as_ref().unwrap().clone();
+            let view = output.view.clone();
 
             let clear = clear.0.clone();
 
@@ -58,6 +58,10 @@ impl Node for ClearNode {
         };
 
         Box::pin(async move {
+            let view = match &view {
+                Some(r) => r.as_ref(),
+                None => return Ok(()),
+            };
             let mut encoder = commands.0.as_ref().borrow_mut();
 
             let depth_stencil_attachment = None;
@@ -65,7 +69,7 @@ impl Node for ClearNode {
                 label: None,
                 depth_stencil_attachment,
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: view.as_ref(),
+                    view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(clear.color),
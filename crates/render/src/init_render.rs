@@ -2,8 +2,8 @@ use std::mem::transmute;
 
 use crate::PiFirstSurface;
 use crate::{
-    graph::graph::RenderGraph, PiAdapterInfo, PiRenderDevice, PiRenderGraph, PiRenderInstance,
-    PiRenderOptions, PiRenderQueue,
+    graph::graph::RenderGraph, OffscreenOptions, PiAdapterInfo, PiOffscreenTarget, PiRenderDevice, PiRenderGraph, PiRenderInstance, PiRenderOutput,
+    PiRenderOptions, PiRenderQueue, PiSurfaceUsages,
 };
 use bevy_ecs::prelude::With;
 use bevy_ecs::world::World;
@@ -30,11 +30,25 @@ pub(crate) fn init_render<A: AsyncRuntime + AsyncRuntimeExt>(
     let primary_window_handle = primary_window.single(world).clone();
     let mode = options.present_mode;
 
-    init_render_impl(world, rt, &primary_window_handle, options);
+    init_render_impl(world, rt, Some(&primary_window_handle), options);
 
     (primary_window_handle.clone(), mode)
 }
 
+/// 离屏模式初始化：不需要窗口，不创建surface，渲染到离屏目标上
+pub(crate) fn init_render_offscreen<A: AsyncRuntime + AsyncRuntimeExt>(
+    world: &mut World,
+    rt: &A,
+    offscreen: OffscreenOptions,
+) {
+    let options = world.resource::<PiRenderOptions>().0.clone();
+    init_render_impl(world, rt, None, options);
+
+    let target = PiOffscreenTarget::new(&world.resource::<PiRenderDevice>().0, offscreen);
+    world.resource_mut::<PiRenderOutput>().set_offscreen(&target);
+    world.insert_resource(target);
+}
+
 // 初始化 渲染环境 的 System
 //
 // A 的 类型 见 plugin 模块
@@ -44,7 +58,7 @@ pub(crate) fn init_render<A: AsyncRuntime + AsyncRuntimeExt>(
 fn init_render_impl<A: AsyncRuntime + AsyncRuntimeExt>(
     world: &mut World,
     rt: &A,
-    window: &HandleWrapper,
+    window: Option<&HandleWrapper>,
     options: RenderOptions,
 ) {
     let backends = options.backends;
@@ -55,7 +69,7 @@ fn init_render_impl<A: AsyncRuntime + AsyncRuntimeExt>(
         gles_minor_version: Gles3MinorVersion::Automatic,
     });
 
-    let surface = window.handle.create_surface(&instance);
+    let surface = window.map(|r| r.handle.create_surface(&instance));
 
     let mut allocator = world.get_resource_mut::<pi_bevy_asset::Allocator>().unwrap();
     let allocator1 = &mut allocator.0;
@@ -66,6 +80,7 @@ fn init_render_impl<A: AsyncRuntime + AsyncRuntimeExt>(
         device,
         queue,
         adapter_info,
+        surface_usages,
     } = rt
         .block_on(setup_render_context(instance, surface, options, allocator1))
         .unwrap();
@@ -84,6 +99,9 @@ fn init_render_impl<A: AsyncRuntime + AsyncRuntimeExt>(
     world.insert_resource(PiRenderDevice(device));
     world.insert_resource(PiRenderQueue(queue));
     world.insert_resource(PiAdapterInfo(adapter_info));
+    if let Some(usages) = surface_usages {
+        world.insert_resource(PiSurfaceUsages(usages));
+    }
 }
 
 #[derive(Default)]
@@ -93,23 +111,30 @@ struct SetupResult<'a> {
     pub device: Option<RenderDevice>,
     pub queue: Option<RenderQueue>,
     pub adapter_info: Option<wgpu::AdapterInfo>,
+    pub surface_usages: Option<wgpu::TextureUsages>,
 }
 
 /// 初始化 渲染 环境
 async fn setup_render_context<'a>(
     instance: RenderInstance,
-    surface: wgpu::Surface<'a>,
+    surface: Option<wgpu::Surface<'a>>,
     options: RenderOptions,
     alloter: &mut Allocator,
 ) -> SetupResult<'a> {
     let request_adapter_options = wgpu::RequestAdapterOptions {
         power_preference: options.power_preference,
-        compatible_surface: Some(&surface),
+        compatible_surface: surface.as_ref(),
         ..Default::default()
     };
     let (device, queue, adapter_info) =
         initialize_renderer(&instance, &options, &request_adapter_options, alloter).await;
 
+    // initialize_renderer不返回adapter，以相同的参数重新取得adapter，查询surface支持的纹理用途
+    let surface_usages = match &surface {
+        Some(surface) => instance.request_adapter(&request_adapter_options).await.map(|adapter| surface.get_capabilities(&adapter).usages),
+        None => None,
+    };
+
     let config = wgpu::SurfaceConfiguration {
         format: wgpu::TextureFormat::pi_render_default(),
         width: 1,
@@ -121,17 +146,20 @@ async fn setup_render_context<'a>(
         desired_maximum_frame_latency: 2,
     };
 
-    surface.configure(&device, &config);
+    if let Some(surface) = &surface {
+        surface.configure(&device, &config);
+    }
 
     debug!("Configured wgpu adapter Limits: {:#?}", device.limits());
     debug!("Configured wgpu adapter Features: {:#?}", device.features());
 
     SetupResult {
-        surface: Some(surface),
+        surface,
         instance: Some(instance),
         device: Some(device),
         queue: Some(queue),
         adapter_info: Some(adapter_info),
+        surface_usages,
     }
 }
 
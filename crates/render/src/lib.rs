@@ -16,6 +16,7 @@ mod init_render;
 mod plugin;
 mod render_windows;
 mod resource;
+mod screenshot;
 mod system;
 
 use std::sync::atomic::AtomicBool;
@@ -28,6 +29,8 @@ pub use plugin::*;
 use render_derive::NodeParam;
 /// 单例
 pub use resource::*;
+/// 截屏
+pub use screenshot::*;
 
 lazy_static! {
     pub static ref IS_RESUMED: AtomicBool = AtomicBool::new(true);
@@ -52,8 +55,8 @@ impl GetTargetView for SimpleInOut {
 }
 
 pub mod asset_config {
-    use bevy_ecs::prelude::Resource;
-    use pi_bevy_asset::AssetCapacity;
+    use bevy_ecs::prelude::{Res, ResMut, Resource, World};
+    use pi_bevy_asset::{AssetCapacity, AssetConfig};
 
     /// Asset 资源管理
     pub enum EAsset {
@@ -72,6 +75,105 @@ pub mod asset_config {
         File,
     }
 
+    impl EAsset {
+        /// 在资产配置（AssetConfig）中的类型名
+        pub fn name(&self) -> &'static str {
+            match self {
+                EAsset::RenderResTextureView => "RENDER_RES_TEXTURE_VIEW",
+                EAsset::RenderResUnuseTexture => "RENDER_RES_UNUSE_TEXTURE",
+                EAsset::TextureRes => "TEXTURE_RES",
+                EAsset::ImageTexture => "IMAGE_TEXTURE",
+                EAsset::ImageTextureView => "IMAGE_TEXTURE_VIEW",
+                EAsset::BindGroup => "BIND_GROUP",
+                EAsset::SamplerRes => "SAMPLER_RES",
+                EAsset::VertexBuffer3D => "VERTEX_BUFFER_3D",
+                EAsset::ShaderMeta3D => "SHADER_META_3D",
+                EAsset::Shader3D => "SHADER_3D",
+                EAsset::RenderPipeline => "RENDER_PIPELINE",
+                EAsset::GLTF => "GLTF",
+                EAsset::File => "FILE",
+            }
+        }
+    }
+
+    /// 资产容量配置单例
+    /// 单例的值取自资产配置（AssetConfig），资产配置修改后由sync_asset_cfg同步；也可以直接通过AssetConfig::capacity_by_name查询
+    pub trait TAssetCfg: Resource + Default + AsRef<AssetCapacity> {
+        const ASSET: EAsset;
+        fn new(capacity: AssetCapacity) -> Self;
+    }
+
+    macro_rules! impl_asset_cfg {
+        ($($ty: ident => $asset: ident),*) => {
+            $(
+                impl TAssetCfg for $ty {
+                    const ASSET: EAsset = EAsset::$asset;
+                    fn new(capacity: AssetCapacity) -> Self {
+                        Self(capacity)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_asset_cfg!(
+        AssetCfgRenderResTextureView => RenderResTextureView,
+        AssetCfgRenderResUnuseTexture => RenderResUnuseTexture,
+        AssetCfgSamplerRes => SamplerRes,
+        AssetCfgTextureRes => TextureRes,
+        AssetCfgImageTexture => ImageTexture,
+        AssetCfgImageTextureView => ImageTextureView,
+        AssetCfgBindGroup => BindGroup,
+        AssetCfgVertexBuffer3D => VertexBuffer3D,
+        AssetCfgShaderMeta3D => ShaderMeta3D,
+        AssetCfgShader3D => Shader3D,
+        AssetCfgRenderPipeline => RenderPipeline
+    );
+
+    /// 以资产配置中的值初始化资产容量配置单例（已存在的单例不会被覆盖）
+    pub fn init_asset_cfg<T: TAssetCfg>(world: &mut World) {
+        if world.contains_resource::<T>() {
+            return;
+        }
+        let default = *T::default().as_ref();
+        let capacity = match world.get_resource_mut::<AssetConfig>() {
+            Some(mut config) => {
+                config.register_key(T::ASSET.name());
+                config.capacity_by_name(T::ASSET.name(), default)
+            }
+            None => default,
+        };
+        world.insert_resource(T::new(capacity));
+    }
+
+    /// 资产配置修改后，将配置了的容量同步到资产容量配置单例上（资产配置中没有该类型时，保留单例原来的值）
+    pub fn sync_asset_cfg<T: TAssetCfg>(config: Res<AssetConfig>, mut cfg: ResMut<T>) {
+        if !config.is_changed() || config.get_by_name(T::ASSET.name()).is_none() {
+            return;
+        }
+        let capacity = config.capacity_by_name(T::ASSET.name(), *cfg.as_ref());
+        *cfg = T::new(capacity);
+    }
+
+    /// 初始化所有资产容量配置单例
+    pub fn init_asset_cfgs(world: &mut World) {
+        init_asset_cfg::<AssetCfgRenderResTextureView>(world);
+        init_asset_cfg::<AssetCfgRenderResUnuseTexture>(world);
+        init_asset_cfg::<AssetCfgSamplerRes>(world);
+        init_asset_cfg::<AssetCfgTextureRes>(world);
+        init_asset_cfg::<AssetCfgImageTexture>(world);
+        init_asset_cfg::<AssetCfgImageTextureView>(world);
+        init_asset_cfg::<AssetCfgBindGroup>(world);
+        init_asset_cfg::<AssetCfgVertexBuffer3D>(world);
+        init_asset_cfg::<AssetCfgShaderMeta3D>(world);
+        init_asset_cfg::<AssetCfgShader3D>(world);
+        init_asset_cfg::<AssetCfgRenderPipeline>(world);
+        if let Some(mut config) = world.get_resource_mut::<AssetConfig>() {
+            config.register_key(EAsset::GLTF.name());
+            config.register_key(EAsset::File.name());
+        }
+    }
+
     #[derive(Resource)]
     pub struct AssetCfgRenderResTextureView(pub AssetCapacity);
     impl Default for AssetCfgRenderResTextureView {
@@ -1,16 +1,17 @@
 use crate::system::build_graph;
 use crate::TextureKeyAlloter;
-use crate::{init_render::init_render, render_windows::RenderWindow, system::run_frame_system,
+use crate::{init_render::{init_render, init_render_offscreen}, render_windows::RenderWindow, OffscreenOptions, system::run_frame_system,
     PiAsyncRuntime, PiClearOptions, PiRenderDevice, PiRenderOptions, PiRenderWindow,
-    PiSafeAtlasAllocator, PiScreenTexture,
+    PiSafeAtlasAllocator, PiScreenTexture, PiRenderOutput,
 };
-use bevy_app::{App, Plugin, PostUpdate, Update};
+use bevy_app::{App, First, Plugin, PostUpdate, Update};
 
 use bevy_ecs::prelude::IntoSystemConfigs;
 use bevy_ecs::schedule::{SystemSet, IntoSystemSetConfig, IntoSystemSetConfigs};
 use pi_assets::asset::GarbageEmpty;
 use pi_async_rt::prelude::*;
-use pi_bevy_asset::{Allocator, AssetConfig, AssetDesc, ShareAssetMgr, ShareHomogeneousMgr};
+use pi_bevy_asset::{Allocator, AssetConfig, ShareAssetMgr, ShareHomogeneousMgr, TAssetCapacity};
+use pi_bevy_ecs_extend::async_system::AsyncSpawner;
 use pi_render::renderer::sampler::SamplerRes;
 use pi_render::{
     components::view::target_alloc::{SafeAtlasAllocator, UnuseTexture},
@@ -21,9 +22,8 @@ use pi_render::{
         pipeline::RenderPipeline,
     },
 };
-use std::mem::size_of;
 use wgpu::TextureView;
-pub use bevy_window::{should_run, FrameState};
+pub use bevy_window::{should_run, should_simulate, FrameState, FrameControl, update_frame_control, run_simulation, SimulationUpdate};
 
 /// ================ 阶段标签 ================
 
@@ -37,7 +37,7 @@ pub struct GraphBuild;
 #[derive(Debug, Clone, Hash, SystemSet, PartialEq, Eq)]
 pub struct GraphRun;
 
-/// 帧数据准备（实际上就是在FrameDataPrepare系统集中的system，添加了FrameState::Active的运行条件）
+/// 帧数据准备（实际上就是在FrameDataPrepare系统集中的system，添加了FrameState::Active及帧控制FrameControl的运行条件，暂停时不运行）
 #[derive(Debug, Clone, Hash, SystemSet, PartialEq, Eq)]
 pub struct FrameDataPrepare;
 
@@ -46,12 +46,14 @@ pub struct FrameDataPrepare;
 #[derive(Default)]
 pub struct PiRenderPlugin {
     pub frame_init_state: FrameState,
+    /// 离屏模式：不需要窗口，渲染图渲染到离屏目标（PiOffscreenTarget）上，用于服务器、测试、缩略图生成等
+    pub offscreen: Option<OffscreenOptions>,
 }
 
 impl Plugin for PiRenderPlugin {
     fn build(&self, app: &mut App) {
 		app
-			.configure_set(Update, FrameDataPrepare.run_if(should_run))
+			.configure_set(Update, FrameDataPrepare.run_if(should_simulate))
 			.configure_set(PostUpdate, PiRenderSystemSet.run_if(should_run))
 			.configure_set(PostUpdate, GraphBuild.in_set(PiRenderSystemSet))
 			.configure_set(PostUpdate, GraphRun.in_set(PiRenderSystemSet))
@@ -71,8 +73,15 @@ impl Plugin for PiRenderPlugin {
 		// });
 
         app.insert_resource(self.frame_init_state);
+        if app.world.get_resource::<FrameControl>().is_none() {
+            app.insert_resource(FrameControl::default());
+        }
+        app.add_systems(First, update_frame_control)
+            // 模拟按帧控制的步数运行，之后再准备帧数据
+            .add_systems(Update, run_simulation.run_if(should_run).before(FrameDataPrepare));
 
         app.insert_resource(PiScreenTexture::default());
+        app.insert_resource(PiRenderOutput::default());
 
         if app.world.get_resource::<PiRenderOptions>().is_none() {
             app.insert_resource(PiRenderOptions::default());
@@ -149,93 +158,54 @@ impl Plugin for PiRenderPlugin {
         ) = {
             let w = app.world.cell();
             let mut allocator = w.get_resource_mut::<Allocator>().unwrap();
-            let asset_config = w.get_resource::<AssetConfig>().unwrap();
+            let mut asset_config = w.get_resource_mut::<AssetConfig>().unwrap();
+            register_asset_names(&mut asset_config);
             (
                 ShareAssetMgr::<RenderRes<TextureView>>::new_with_config(
                     GarbageEmpty(),
-                    &AssetDesc {
-                        ref_garbage: false,
-                        min: 30 * 1024 * 1024,
-                        max: 600 * 1024 * 1024,
-                        timeout: 10 * 60 * 1000,
-                    },
+                    &<RenderRes<TextureView> as TAssetCapacity>::capacity().into(),
                     &asset_config,
                     &mut allocator,
                 ),
                 ShareHomogeneousMgr::<RenderRes<UnuseTexture>>::new_with_config(
                     pi_assets::homogeneous::GarbageEmpty(),
-                    &AssetDesc {
-                        ref_garbage: false,
-                        min: 10 * size_of::<UnuseTexture>(),
-                        max: 20 * size_of::<UnuseTexture>(),
-                        timeout: 10 * 60 * 1000,
-                    },
+                    &<RenderRes<UnuseTexture> as TAssetCapacity>::capacity().into(),
                     &asset_config,
                     &mut allocator,
                 ),
                 ShareAssetMgr::<RenderRes<Buffer>>::new_with_config(
                     GarbageEmpty(),
-                    &AssetDesc {
-                        ref_garbage: false,
-                        min: 10 * 1024 * 1024,
-                        max: 50 * 1024 * 1024,
-                        timeout: 10 * 60 * 1000,
-                    },
+                    &<RenderRes<Buffer> as TAssetCapacity>::capacity().into(),
                     &asset_config,
                     &mut allocator,
                 ),
                 ShareAssetMgr::<SamplerRes>::new_with_config(
                     GarbageEmpty(),
-                    &AssetDesc {
-                        ref_garbage: false,
-                        min: 10 * 1024,
-                        max: 20 * 1024,
-                        timeout: 10 * 60 * 1000,
-                    },
+                    &<SamplerRes as TAssetCapacity>::capacity().into(),
                     &asset_config,
                     &mut allocator,
                 ),
                 ShareAssetMgr::<RenderRes<BindGroup>>::new_with_config(
                     GarbageEmpty(),
-                    &AssetDesc {
-                        ref_garbage: false,
-                        min: 5 * 1024 * 1024,
-                        max: 10 * 1024 * 1024,
-                        timeout: 10 * 60 * 1000,
-                    },
+                    &<RenderRes<BindGroup> as TAssetCapacity>::capacity().into(),
                     &asset_config,
                     &mut allocator,
                 ),
                 ShareAssetMgr::<TextureRes>::new_with_config(
                     GarbageEmpty(),
-                    &AssetDesc {
-                        ref_garbage: false,
-                        min: 10 * 1024 * 1024,
-                        max: 600 * 1024 * 1024,
-                        timeout: 10 * 60 * 1000,
-                    },
+                    &<TextureRes as TAssetCapacity>::capacity().into(),
                     &asset_config,
                     &mut allocator,
                 ),
 				ShareAssetMgr::<AssetWithId<TextureRes>>::new_with_config(
                     GarbageEmpty(),
-                    &AssetDesc {
-                        ref_garbage: false,
-                        min: 10 * 1024 * 1024,
-                        max: 600 * 1024 * 1024,
-                        timeout: 10 * 60 * 1000,
-                    },
+                    &<AssetWithId<TextureRes> as TAssetCapacity>::capacity().into(),
                     &asset_config,
                     &mut allocator,
                 ),
                 ShareAssetMgr::<RenderRes<RenderPipeline>>::new_with_config(
                     GarbageEmpty(),
-                    &AssetDesc {
-                        ref_garbage: false,
-                        min: 5 * 1024 * 1024,
-                        max: 10 * 1024 * 1024,
-                        timeout: 10 * 60 * 1000,
-                    },
+                    &<RenderRes<RenderPipeline> as TAssetCapacity>::capacity().into(),
                     &asset_config,
                     &mut allocator,
                 ),
@@ -245,7 +215,28 @@ impl Plugin for PiRenderPlugin {
         app.insert_resource(share_texture_res.clone());
         app.insert_resource(share_unuse.clone());
 
+        // 资产容量配置单例（从统一的资产配置中取值）
+        crate::asset_config::init_asset_cfgs(&mut app.world);
+        {
+            use crate::asset_config::*;
+            app.add_systems(First, (
+                sync_asset_cfg::<AssetCfgRenderResTextureView>,
+                sync_asset_cfg::<AssetCfgRenderResUnuseTexture>,
+                sync_asset_cfg::<AssetCfgSamplerRes>,
+                sync_asset_cfg::<AssetCfgTextureRes>,
+                sync_asset_cfg::<AssetCfgImageTexture>,
+                sync_asset_cfg::<AssetCfgImageTextureView>,
+                sync_asset_cfg::<AssetCfgBindGroup>,
+                sync_asset_cfg::<AssetCfgVertexBuffer3D>,
+                sync_asset_cfg::<AssetCfgShaderMeta3D>,
+                sync_asset_cfg::<AssetCfgShader3D>,
+                sync_asset_cfg::<AssetCfgRenderPipeline>,
+            ));
+        }
+
         app.insert_resource(PiAsyncRuntime(rt.clone()));
+        // 异步system在PiAsyncRuntime上运行
+        app.insert_resource(AsyncSpawner::new(rt.clone()));
 
         // 添加资源管理器单例
         app.insert_resource(buffer_res);
@@ -265,9 +256,13 @@ impl Plugin for PiRenderPlugin {
         // 	3 * 60 * 1000,
         // ));
 
-        let (wrapper, present_mode) = init_render(&mut app.world, &rt);
-
-        app.insert_resource(PiRenderWindow(RenderWindow::new(wrapper, present_mode)));
+        match &self.offscreen {
+            Some(offscreen) => init_render_offscreen(&mut app.world, &rt, offscreen.clone()),
+            None => {
+                let (wrapper, present_mode) = init_render(&mut app.world, &rt);
+                app.insert_resource(PiRenderWindow(RenderWindow::new(wrapper, present_mode)));
+            }
+        }
         let texture_key_alloter = TextureKeyAlloter::default();
         app.insert_resource(texture_key_alloter.clone());
 
@@ -281,6 +276,18 @@ impl Plugin for PiRenderPlugin {
     }
 }
 
+// 注册渲染库创建的资产管理器的类型名
+fn register_asset_names(config: &mut AssetConfig) {
+    config.register::<RenderRes<TextureView>>();
+    config.register::<RenderRes<UnuseTexture>>();
+    config.register::<RenderRes<Buffer>>();
+    config.register::<RenderRes<BindGroup>>();
+    config.register::<AssetWithId<TextureRes>>();
+    config.register::<SamplerRes>();
+    config.register::<TextureRes>();
+    config.register::<RenderRes<RenderPipeline>>();
+}
+
 #[cfg(target_arch = "wasm32")]
 fn create_single_runtime() -> pi_async_rt::rt::serial_local_compatible_wasm_runtime::LocalTaskRuntime
 {
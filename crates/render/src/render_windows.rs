@@ -11,6 +11,8 @@ pub struct RenderWindow {
     pub height: u32,
     pub handle: HandleWrapper,
     pub present_mode: PresentMode,
+    /// 屏幕纹理的用途，截屏时需要COPY_SRC
+    pub usage: wgpu::TextureUsages,
 }
 
 impl RenderWindow {
@@ -18,6 +20,7 @@ impl RenderWindow {
         Self {
             handle,
             present_mode,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             width: 0,
             height: 0,
         }
@@ -58,7 +61,7 @@ pub fn prepare_window(
         format: TextureFormat::pi_render_default(),
         width,
         height,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        usage: window.usage,
         present_mode: window.present_mode,
         alpha_mode: wgpu::CompositeAlphaMode::Auto,
         view_formats: vec![],
@@ -3,6 +3,7 @@ use bevy_ecs::prelude::FromWorld;
 use bevy_ecs::system::Resource;
 use pi_async_rt::prelude::*;
 use pi_render::rhi::buffer_alloc::BufferAlloter;
+use pi_render::rhi::texture::PiRenderDefault;
 use pi_share::Share;
 use wgpu::BufferUsages;
 use derive_deref::{Deref, DerefMut};
@@ -89,6 +90,10 @@ unsafe impl Sync for PiRenderQueue {}
 #[derive(Resource, Deref, DerefMut)]
 pub struct PiAdapterInfo(pub pi_render::rhi::AdapterInfo);
 
+/// surface支持的纹理用途（surface.get_capabilities(adapter).usages），离屏模式下没有该单例
+#[derive(Resource, Deref, Clone, Copy, Debug)]
+pub struct PiSurfaceUsages(pub wgpu::TextureUsages);
+
 /// 渲染图，等价于 RenderGraph
 #[derive(Resource, Deref, DerefMut)]
 pub struct PiRenderGraph(pub super::graph::graph::RenderGraph);
@@ -105,6 +110,122 @@ pub struct PiScreenTexture(pub Option<pi_render::rhi::texture::ScreenTexture>);
 unsafe impl Send for PiScreenTexture {}
 unsafe impl Sync for PiScreenTexture {}
 
+/// 离屏渲染（无窗口）参数
+#[derive(Debug, Clone)]
+pub struct OffscreenOptions {
+    pub width: u32,
+    pub height: u32,
+    pub format: wgpu::TextureFormat,
+}
+
+impl Default for OffscreenOptions {
+    fn default() -> Self {
+        Self {
+            width: 1024,
+            height: 1024,
+            format: wgpu::TextureFormat::pi_render_default(),
+        }
+    }
+}
+
+/// 离屏渲染目标，离屏模式下代替屏幕纹理（此时PiScreenTexture为None）
+#[derive(Resource)]
+pub struct PiOffscreenTarget {
+    pub options: OffscreenOptions,
+    pub texture: Share<wgpu::Texture>,
+    pub view: Share<wgpu::TextureView>,
+}
+
+// TODO Send问题， 临时解决
+unsafe impl Send for PiOffscreenTarget {}
+unsafe impl Sync for PiOffscreenTarget {}
+
+impl PiOffscreenTarget {
+    pub fn new(device: &pi_render::rhi::device::RenderDevice, options: OffscreenOptions) -> Self {
+        let texture = device.wgpu_device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("offscreen target"),
+            size: wgpu::Extent3d {
+                width: options.width,
+                height: options.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: options.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self {
+            options,
+            texture: Share::new(texture),
+            view: Share::new(view),
+        }
+    }
+
+    /// 修改尺寸，重新创建纹理
+    pub fn resize(&mut self, device: &pi_render::rhi::device::RenderDevice, width: u32, height: u32) {
+        if width == self.options.width && height == self.options.height {
+            return;
+        }
+        let mut options = self.options.clone();
+        options.width = width;
+        options.height = height;
+        *self = Self::new(device, options);
+    }
+
+    pub fn width(&self) -> u32 {
+        self.options.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.options.height
+    }
+}
+
+/// 渲染输出：窗口模式下为屏幕纹理，离屏模式下为离屏目标
+/// 只关心输出目标的节点、system应使用该单例，而不是直接访问PiScreenTexture、PiRenderWindow
+#[derive(Resource)]
+pub struct PiRenderOutput {
+    /// 当前帧的输出视图（窗口模式下只在帧推期间有效）
+    pub view: Option<Share<wgpu::TextureView>>,
+    pub width: u32,
+    pub height: u32,
+    pub format: wgpu::TextureFormat,
+}
+
+// TODO Send问题， 临时解决
+unsafe impl Send for PiRenderOutput {}
+unsafe impl Sync for PiRenderOutput {}
+
+impl Default for PiRenderOutput {
+    fn default() -> Self {
+        Self {
+            view: None,
+            width: 0,
+            height: 0,
+            format: wgpu::TextureFormat::pi_render_default(),
+        }
+    }
+}
+
+impl PiRenderOutput {
+    pub fn set(&mut self, view: Option<Share<wgpu::TextureView>>, width: u32, height: u32, format: wgpu::TextureFormat) {
+        self.view = view;
+        self.width = width;
+        self.height = height;
+        self.format = format;
+    }
+
+    /// 输出到离屏目标
+    pub fn set_offscreen(&mut self, target: &PiOffscreenTarget) {
+        self.set(Some(target.view.clone()), target.width(), target.height(), target.options.format);
+    }
+}
+
 /// 清屏 参数
 #[derive(Default, Resource, Deref, DerefMut, Debug, Clone)]
 pub struct PiClearOptions(pub ClearOptions);
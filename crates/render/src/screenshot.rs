@@ -0,0 +1,289 @@
+//! 截屏
+//! 发送ScreenshotRequest事件后，在下一次渲染图运行后（窗口模式在呈现之前），将最终的渲染目标（屏幕纹理或离屏目标）拷贝到buffer，
+//! buffer异步映射完成后，转换为RGBA图像（去掉行对齐的填充，BGRA转为RGBA），以ScreenshotCaptured事件发出；
+//! 请求指定了路径时，同时保存为png文件（在后台任务中编码、写文件，完成后才发出该次截屏的事件，不阻塞帧推）；
+//! 渲染目标为空（宽或高为0）、格式不支持或不能作为拷贝源（surface不支持COPY_SRC）时，以带error的ScreenshotCaptured事件发出
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_ecs::{
+    event::{Event, EventReader, EventWriter, Events},
+    prelude::World,
+    schedule::IntoSystemConfigs,
+    system::{Res, Resource},
+};
+use pi_bevy_ecs_extend::async_system::{AsyncTasks, AsyncTasksPlugin};
+use pi_share::{Share, ShareMutex};
+
+use crate::{GraphRun, PiRenderDevice, PiRenderQueue, PiRenderWindow, PiSurfaceUsages};
+
+/// 截屏请求
+#[derive(Debug, Clone, Default, Event)]
+pub struct ScreenshotRequest {
+    /// 保存png的路径，None时不保存
+    pub path: Option<PathBuf>,
+}
+
+/// 截屏完成
+#[derive(Debug, Clone, Event)]
+pub struct ScreenshotCaptured {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA8图像，每行width * 4字节
+    pub data: Share<Vec<u8>>,
+    pub path: Option<PathBuf>,
+    /// 截屏或保存png失败的原因
+    pub error: Option<String>,
+}
+
+// TODO Send问题， 临时解决
+unsafe impl Send for ScreenshotCaptured {}
+unsafe impl Sync for ScreenshotCaptured {}
+
+// 拷贝中的截屏
+struct Capture {
+    buffer: Share<wgpu::Buffer>,
+    width: u32,
+    height: u32,
+    // 对齐后的每行字节数
+    padded_row: u32,
+    bgra: bool,
+    requests: Vec<ScreenshotRequest>,
+    // 映射完成（成功或失败）
+    mapped: Share<AtomicBool>,
+    error: Share<ShareMutex<Option<String>>>,
+}
+
+/// 截屏请求和拷贝中的截屏
+#[derive(Resource, Default)]
+pub struct PiScreenshots {
+    // 等待渲染图运行后拷贝的请求
+    pending: ShareMutex<Vec<ScreenshotRequest>>,
+    captures: ShareMutex<Vec<Capture>>,
+    // 无法拷贝的请求及原因，下次发出截屏事件时以失败发出
+    failed: ShareMutex<Vec<(ScreenshotRequest, String)>>,
+}
+
+// TODO Send问题， 临时解决
+unsafe impl Send for PiScreenshots {}
+unsafe impl Sync for PiScreenshots {}
+
+impl PiScreenshots {
+    /// 是否有等待拷贝的请求
+    pub fn has_pending(&self) -> bool {
+        !self.pending.lock().is_empty()
+    }
+}
+
+/// 截屏插件
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        // 保存png在后台任务中进行
+        if !app.is_plugin_added::<AsyncTasksPlugin>() {
+            app.add_plugins(AsyncTasksPlugin);
+        }
+        app.init_resource::<PiScreenshots>()
+            .add_event::<ScreenshotRequest>()
+            .add_event::<ScreenshotCaptured>()
+            .add_systems(PostUpdate, (accept_screenshot_requests.before(GraphRun), deliver_screenshots.after(GraphRun)));
+    }
+
+    fn finish(&self, app: &mut App) {
+        // 屏幕纹理需要能作为拷贝源，surface不支持时，窗口模式的截屏以失败发出
+        let usages = app.world.get_resource::<PiSurfaceUsages>().map(|r| r.0);
+        if let Some(mut window) = app.world.get_resource_mut::<PiRenderWindow>() {
+            match usages {
+                Some(usages) if usages.contains(wgpu::TextureUsages::COPY_SRC) => window.0.usage |= wgpu::TextureUsages::COPY_SRC,
+                _ => log::warn!("surface does not support COPY_SRC, screenshot is unavailable, usages: {:?}", usages),
+            }
+        }
+    }
+}
+
+/// 接收截屏请求，在渲染图运行后拷贝
+pub fn accept_screenshot_requests(mut requests: EventReader<ScreenshotRequest>, screenshots: Res<PiScreenshots>) {
+    let mut pending = screenshots.pending.lock();
+    for r in requests.iter() {
+        pending.push(r.clone());
+    }
+}
+
+/// 将渲染目标拷贝到buffer，并开始映射（渲染图运行后调用）
+pub(crate) fn capture_texture(world: &World, texture: &wgpu::Texture, width: u32, height: u32, format: wgpu::TextureFormat) {
+    let screenshots = match world.get_resource::<PiScreenshots>() {
+        Some(r) => r,
+        None => return,
+    };
+    let requests = std::mem::take(&mut *screenshots.pending.lock());
+    if requests.is_empty() {
+        return;
+    }
+
+    let fail = |error: String| {
+        log::warn!("screenshot fail, {}", error);
+        screenshots.failed.lock().extend(requests.iter().map(|r| (r.clone(), error.clone())));
+    };
+    if width == 0 || height == 0 {
+        return fail(format!("empty target, width: {}, height: {}", width, height));
+    }
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        return fail(format!("target does not support COPY_SRC, usage: {:?}", texture.usage()));
+    }
+    let bgra = match format {
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        _ => return fail(format!("unsupported format: {:?}", format)),
+    };
+
+    let device = world.resource::<PiRenderDevice>().0.wgpu_device();
+    let queue = &world.resource::<PiRenderQueue>().0;
+
+    // 每行字节数需要对齐到COPY_BYTES_PER_ROW_ALIGNMENT
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_row = (width * 4 + align - 1) / align * align;
+    let buffer = Share::new(device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("screenshot"),
+        size: padded_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    }));
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("screenshot") });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let mapped = Share::new(AtomicBool::new(false));
+    let error = Share::new(ShareMutex::new(None));
+    let (mapped1, error1) = (mapped.clone(), error.clone());
+    buffer.slice(..).map_async(wgpu::MapMode::Read, move |r| {
+        if let Err(e) = r {
+            *error1.lock() = Some(format!("{:?}", e));
+        }
+        mapped1.store(true, Ordering::Release);
+    });
+
+    screenshots.captures.lock().push(Capture { buffer, width, height, padded_row, bgra, requests, mapped, error });
+}
+
+/// 发出映射完成的截屏
+pub fn deliver_screenshots(
+    screenshots: Res<PiScreenshots>,
+    device: Res<PiRenderDevice>,
+    tasks: AsyncTasks,
+    mut events: EventWriter<ScreenshotCaptured>,
+) {
+    for (request, error) in std::mem::take(&mut *screenshots.failed.lock()) {
+        events.send(ScreenshotCaptured {
+            width: 0,
+            height: 0,
+            data: Share::new(Vec::new()),
+            path: request.path,
+            error: Some(error),
+        });
+    }
+
+    let mut captures = screenshots.captures.lock();
+    if captures.is_empty() {
+        return;
+    }
+    device.0.wgpu_device().poll(wgpu::Maintain::Poll);
+
+    let mut i = 0;
+    while i < captures.len() {
+        if !captures[i].mapped.load(Ordering::Acquire) {
+            i += 1;
+            continue;
+        }
+        let capture = captures.swap_remove(i);
+        let error = capture.error.lock().take();
+        let data = if error.is_none() {
+            let data = read_rgba(&capture);
+            capture.buffer.unmap();
+            data
+        } else {
+            Vec::new()
+        };
+        let (width, height, requests) = (capture.width, capture.height, capture.requests);
+
+        // 需要保存png时，在后台任务中编码、写文件，完成后在同步点发出事件
+        if error.is_none() && requests.iter().any(|r| r.path.is_some()) {
+            tasks.spawn(async move {
+                let captured = save_requests(width, height, data, requests);
+                Box::new(move |world: &mut World| match world.get_resource_mut::<Events<ScreenshotCaptured>>() {
+                    Some(mut events) => events.extend(captured),
+                    None => log::warn!("event is not registered: ScreenshotCaptured"),
+                }) as Box<dyn FnOnce(&mut World) + Send>
+            });
+            continue;
+        }
+
+        let data = Share::new(data);
+        for request in requests {
+            events.send(ScreenshotCaptured {
+                width,
+                height,
+                data: data.clone(),
+                path: request.path,
+                error: error.clone(),
+            });
+        }
+    }
+}
+
+// 保存png，返回每个请求的截屏事件
+fn save_requests(width: u32, height: u32, data: Vec<u8>, requests: Vec<ScreenshotRequest>) -> Vec<ScreenshotCaptured> {
+    let data = Share::new(data);
+    requests
+        .into_iter()
+        .map(|request| {
+            let error = match &request.path {
+                Some(path) => save_png(path, width, height, &data).err().map(|e| {
+                    log::warn!("save screenshot fail, path: {:?}, err: {:?}", path, e);
+                    e
+                }),
+                None => None,
+            };
+            ScreenshotCaptured { width, height, data: data.clone(), path: request.path, error }
+        })
+        .collect()
+}
+
+// 去掉每行的对齐填充，BGRA转为RGBA
+fn read_rgba(capture: &Capture) -> Vec<u8> {
+    let range = capture.buffer.slice(..).get_mapped_range();
+    let row = capture.width as usize * 4;
+    let mut data = Vec::with_capacity(row * capture.height as usize);
+    for chunk in range.chunks(capture.padded_row as usize).take(capture.height as usize) {
+        data.extend_from_slice(&chunk[..row]);
+    }
+    if capture.bgra {
+        for pixel in data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    data
+}
+
+fn save_png(path: &PathBuf, width: u32, height: u32, data: &[u8]) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(data).map_err(|e| e.to_string())
+}
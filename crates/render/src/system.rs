@@ -4,12 +4,14 @@ use crate::{
     graph::graph::RenderGraph,
     render_windows::{prepare_window, RenderWindow},
     PiAsyncRuntime, PiRenderDevice, PiRenderGraph, PiRenderInstance,
-    PiRenderWindow, IS_RESUMED, PiScreenTexture,
+    PiRenderWindow, IS_RESUMED, PiScreenTexture, PiOffscreenTarget, PiRenderOutput,
 };
-use bevy_ecs::prelude::{World, With};
+use bevy_ecs::prelude::{Mut, World, With};
 use bevy_window::{PrimaryWindow, Window};
 use pi_async_rt::prelude::*;
-use pi_render::rhi::texture::ScreenTexture;
+use pi_render::rhi::texture::{PiRenderDefault, ScreenTexture};
+use wgpu::TextureFormat;
+use crate::screenshot::capture_texture;
 #[cfg(feature = "trace")]
 use tracing::Instrument;
 
@@ -25,6 +27,10 @@ pub(crate) fn run_frame_system<A: AsyncRuntime + AsyncRuntimeExt>(world: &mut Wo
     if !IS_RESUMED.load(Ordering::Relaxed){
         return;
     }
+    if world.contains_resource::<PiOffscreenTarget>() {
+        run_offscreen_frame::<A>(world);
+        return;
+    }
     let mut primary_window = world.query_filtered::<&Window, With<PrimaryWindow>>();
 
     let (width, height) = match primary_window.get_single(world) {
@@ -69,12 +75,19 @@ pub(crate) fn run_frame_system<A: AsyncRuntime + AsyncRuntimeExt>(world: &mut Wo
         std::mem::transmute(rg)
     };
 
+    let output: &'static mut PiRenderOutput = unsafe {
+        let w = &mut *(ptr_world as *mut World);
+        let output = w.resource_mut::<PiRenderOutput>().into_inner();
+        std::mem::transmute(output)
+    };
+
     let rt_clone = rt.clone(); 
 
     #[cfg(not(feature = "trace"))]
     let task = async move {
         // ============ 1. 获取 窗口 可用纹理 ============
         prepare_window(window, first_surface, view, device, instance, width, height).unwrap();
+        output.set(view.as_ref().and_then(|r| r.view.clone()), width, height, TextureFormat::pi_render_default());
         // ============ 2. 执行渲染图 ============
         // rg.build().unwrap();
 		// log::warn!("run before====================");
@@ -85,6 +98,7 @@ pub(crate) fn run_frame_system<A: AsyncRuntime + AsyncRuntimeExt>(world: &mut Wo
         // ============ 3. 呈现 ============
 		// log::warn!("take_surface_texture before====================");
         if let Some(view) = view.as_mut().unwrap().take_surface_texture() {
+            capture_texture(world_ref, &view.texture, width, height, TextureFormat::pi_render_default());
             view.present();
         }
     };
@@ -94,6 +108,7 @@ pub(crate) fn run_frame_system<A: AsyncRuntime + AsyncRuntimeExt>(world: &mut Wo
         // ============ 1. 获取 窗口 可用纹理 ============
         async {
             prepare_window(window, first_surface, view, device, instance, width, height).unwrap();
+            output.set(view.as_ref().and_then(|r| r.view.clone()), width, height, TextureFormat::pi_render_default());
         }
         .instrument(prepare_window_span)
         .await;
@@ -123,6 +138,7 @@ pub(crate) fn run_frame_system<A: AsyncRuntime + AsyncRuntimeExt>(world: &mut Wo
 			.instrument(take_texture_span)
 			.await;
 			if let Some(view) = view {
+				capture_texture(world_ref, &view.texture, width, height, TextureFormat::pi_render_default());
 				let system_present_span = tracing::warn_span!("present");
 				let r = async move {
 					view.present();
@@ -133,11 +149,37 @@ pub(crate) fn run_frame_system<A: AsyncRuntime + AsyncRuntimeExt>(world: &mut Wo
 		};
 		rt.block_on(present).unwrap();
 	}
-	
+	// 呈现后屏幕纹理不再可用
+	unsafe { &mut *(ptr_world as *mut World) }.resource_mut::<PiRenderOutput>().view = None;
 
 }
 
 
+// 离屏模式的帧推：没有窗口纹理需要准备和呈现，只执行渲染图
+fn run_offscreen_frame<A: AsyncRuntime + AsyncRuntimeExt>(world: &mut World) {
+    let ptr_world = world as *mut World as usize;
+    let world_ref: &'static World = unsafe { std::mem::transmute(world) };
+    let world_mut: &'static mut World = unsafe { &mut *(world_ref as *const World as usize as *mut World) };
+    let rt = &world_ref.resource::<PiAsyncRuntime<A>>().0;
+    let rg: &'static mut RenderGraph = unsafe {
+        let w = &mut *(ptr_world as *mut World);
+        let rg = &mut w.resource_mut::<PiRenderGraph>().0;
+        std::mem::transmute(rg)
+    };
+
+    unsafe { &mut *(ptr_world as *mut World) }.resource_scope(|w, mut output: Mut<PiRenderOutput>| {
+        output.set_offscreen(w.resource::<PiOffscreenTarget>());
+    });
+
+    let rt_clone = rt.clone();
+    rt.block_on(async move {
+        rg.run(&rt_clone, world_mut).await.unwrap();
+    }).unwrap();
+
+    let target = world_ref.resource::<PiOffscreenTarget>();
+    capture_texture(world_ref, &target.texture, target.width(), target.height(), target.options.format);
+}
+
 pub(crate) fn build_graph<A: AsyncRuntime + AsyncRuntimeExt>(world: &mut World) {
     if !IS_RESUMED.load(Ordering::Relaxed){
         return;
@@ -0,0 +1,278 @@
+use std::time::Duration;
+
+use bevy_ecs::{
+	prelude::World,
+	schedule::ScheduleLabel,
+	system::{ResMut, Resource},
+};
+use pi_time::Instant;
+
+/// 帧控制，支持暂停、暂停时单步推进、固定步长追帧和时间缩放
+///
+/// 每帧开始时（[`update_frame_control`]）计算本帧需要推进的步数[`FrameControl::steps`]和推进的时间[`FrameControl::delta`]，
+/// [`run_simulation`]将[`SimulationUpdate`]调度运行`steps`次，每次推进[`FrameControl::step_delta`]；
+/// 帧控制只影响模拟（SimulationUpdate和帧数据准备，见[`should_simulate`](crate::should_simulate)），渲染和事件清理每帧照常运行（暂停时画面仍会刷新）。
+/// 固定步长模式下，一帧内积累的步数不超过`max_steps`，超出的积累时间被丢弃
+#[derive(Debug, Clone, Resource)]
+pub struct FrameControl {
+	/// 时间缩放
+	pub time_scale: f32,
+	/// 固定步长， None表示每帧推进一步，推进时间为实际流逝的时间
+	pub fixed_timestep: Option<Duration>,
+	/// 固定步长模式下，一帧最多追赶的步数
+	pub max_steps: usize,
+
+	paused: bool,
+	// 暂停时，剩余需要单步推进的帧数
+	step_frames: usize,
+	accumulator: Duration,
+	last_time: Option<Instant>,
+
+	steps: usize,
+	delta: Duration,
+	// 正在运行的步（从0开始）
+	current_step: usize,
+}
+
+impl Default for FrameControl {
+	fn default() -> Self {
+		Self {
+			time_scale: 1.0,
+			fixed_timestep: None,
+			max_steps: 4,
+			paused: false,
+			step_frames: 0,
+			accumulator: Duration::ZERO,
+			last_time: None,
+			steps: 1,
+			delta: Duration::ZERO,
+			current_step: 0,
+		}
+	}
+}
+
+impl FrameControl {
+	/// 以固定步长创建
+	pub fn with_fixed_timestep(timestep: Duration, max_steps: usize) -> Self {
+		Self {
+			fixed_timestep: Some(timestep),
+			max_steps,
+			..Default::default()
+		}
+	}
+
+	/// 暂停
+	pub fn pause(&mut self) {
+		self.paused = true;
+		self.step_frames = 0;
+	}
+
+	/// 恢复运行
+	pub fn resume(&mut self) {
+		self.paused = false;
+		self.step_frames = 0;
+		self.accumulator = Duration::ZERO;
+	}
+
+	#[inline]
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+
+	/// 暂停状态下，单步推进n帧
+	pub fn step(&mut self, n: usize) {
+		self.step_frames += n;
+	}
+
+	/// 本帧推进的步数
+	#[inline]
+	pub fn steps(&self) -> usize {
+		self.steps
+	}
+
+	/// 本帧推进的时间（已缩放）
+	#[inline]
+	pub fn delta(&self) -> Duration {
+		self.delta
+	}
+
+	/// 每步推进的时间（已缩放），固定步长模式下为步长
+	#[inline]
+	pub fn step_delta(&self) -> Duration {
+		match self.fixed_timestep {
+			Some(timestep) if !timestep.is_zero() => timestep,
+			_ => self.delta,
+		}
+	}
+
+	/// 正在运行的步（从0开始），只在SimulationUpdate中有意义
+	#[inline]
+	pub fn current_step(&self) -> usize {
+		self.current_step
+	}
+
+	/// 本帧是否需要推进模拟
+	#[inline]
+	pub fn should_simulate(&self) -> bool {
+		self.steps > 0
+	}
+
+	/// 以实际流逝的时间推进一帧，计算本帧的步数和推进时间
+	pub fn advance(&mut self, real_delta: Duration) {
+		let scaled = real_delta.mul_f32(self.time_scale.max(0.0));
+
+		if self.paused {
+			if self.step_frames > 0 {
+				self.step_frames -= 1;
+				self.steps = 1;
+				self.delta = self.fixed_timestep.unwrap_or(scaled);
+			} else {
+				self.steps = 0;
+				self.delta = Duration::ZERO;
+			}
+			return;
+		}
+
+		match self.fixed_timestep {
+			Some(timestep) if !timestep.is_zero() => {
+				self.accumulator += scaled;
+				let steps = (self.accumulator.as_nanos() / timestep.as_nanos()) as usize;
+				if steps > self.max_steps {
+					// 追赶不上，丢弃积累的时间
+					self.accumulator = Duration::ZERO;
+				} else {
+					self.accumulator -= timestep * steps as u32;
+				}
+				self.steps = steps.min(self.max_steps);
+				self.delta = timestep * self.steps as u32;
+			}
+			_ => {
+				self.steps = 1;
+				self.delta = scaled;
+			}
+		}
+	}
+}
+
+/// 模拟调度，每帧运行[`FrameControl::steps`]次
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimulationUpdate;
+
+/// 按帧控制的步数运行SimulationUpdate调度（没有FrameControl时运行一次）
+pub fn run_simulation(world: &mut World) {
+	let steps = world.get_resource::<FrameControl>().map_or(1, |r| r.steps);
+	for i in 0..steps {
+		if let Some(mut control) = world.get_resource_mut::<FrameControl>() {
+			control.current_step = i;
+		}
+		if world.try_run_schedule(SimulationUpdate).is_err() {
+			// 没有添加任何模拟system
+			return;
+		}
+	}
+}
+
+/// 每帧开始时，推进帧控制
+pub fn update_frame_control(mut control: ResMut<FrameControl>) {
+	let now = Instant::now();
+	let real_delta = match control.last_time {
+		Some(last) => now - last,
+		None => Duration::ZERO,
+	};
+	control.last_time = Some(now);
+	control.advance(real_delta);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MS: Duration = Duration::from_millis(1);
+
+	#[test]
+	fn variable_step() {
+		let mut control = FrameControl::default();
+		control.advance(MS * 16);
+		assert_eq!(control.steps(), 1);
+		assert_eq!(control.delta(), MS * 16);
+		assert_eq!(control.step_delta(), MS * 16);
+
+		control.time_scale = 0.5;
+		control.advance(MS * 16);
+		assert_eq!(control.steps(), 1);
+		assert_eq!(control.delta(), MS * 8);
+	}
+
+	#[test]
+	fn pause_and_step() {
+		let mut control = FrameControl::default();
+		control.pause();
+		control.advance(MS * 16);
+		assert_eq!(control.steps(), 0);
+		assert_eq!(control.delta(), Duration::ZERO);
+		assert!(!control.should_simulate());
+
+		control.step(2);
+		control.advance(MS * 16);
+		assert_eq!(control.steps(), 1);
+		control.advance(MS * 16);
+		assert_eq!(control.steps(), 1);
+		control.advance(MS * 16);
+		assert_eq!(control.steps(), 0);
+
+		control.resume();
+		control.advance(MS * 16);
+		assert_eq!(control.steps(), 1);
+	}
+
+	#[test]
+	fn fixed_step_accumulates() {
+		let mut control = FrameControl::with_fixed_timestep(MS * 10, 4);
+		control.advance(MS * 25);
+		assert_eq!(control.steps(), 2);
+		assert_eq!(control.delta(), MS * 20);
+		assert_eq!(control.step_delta(), MS * 10);
+
+		// 剩余的5ms与本帧的5ms凑成一步
+		control.advance(MS * 5);
+		assert_eq!(control.steps(), 1);
+
+		control.advance(MS * 3);
+		assert_eq!(control.steps(), 0);
+		assert_eq!(control.delta(), Duration::ZERO);
+	}
+
+	#[test]
+	fn fixed_step_drops_overflow() {
+		let mut control = FrameControl::with_fixed_timestep(MS * 10, 4);
+		control.advance(MS * 1000);
+		assert_eq!(control.steps(), 4);
+		assert_eq!(control.delta(), MS * 40);
+
+		// 超出的积累时间已被丢弃
+		control.advance(MS * 5);
+		assert_eq!(control.steps(), 0);
+	}
+
+	#[test]
+	fn run_simulation_steps() {
+		#[derive(Resource, Default)]
+		struct Count(usize);
+
+		let mut world = World::new();
+		world.init_resource::<Count>();
+		world.insert_resource(FrameControl::with_fixed_timestep(MS * 10, 4));
+		let mut schedule = bevy_ecs::schedule::Schedule::new();
+		schedule.add_systems(|mut count: ResMut<Count>| count.0 += 1);
+		world.add_schedule(schedule, SimulationUpdate);
+
+		world.resource_mut::<FrameControl>().advance(MS * 30);
+		run_simulation(&mut world);
+		assert_eq!(world.resource::<Count>().0, 3);
+
+		world.resource_mut::<FrameControl>().pause();
+		world.resource_mut::<FrameControl>().advance(MS * 30);
+		run_simulation(&mut world);
+		assert_eq!(world.resource::<Count>().0, 3);
+	}
+}
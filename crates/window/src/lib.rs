@@ -3,6 +3,7 @@
 #[warn(missing_docs)]
 mod cursor;
 mod event;
+mod frame_control;
 mod raw_handle;
 mod system;
 mod window;
@@ -12,6 +13,7 @@ pub use crate::raw_handle::*;
 use bevy_ecs::{event::{Event, Events}, system::{Resource, Res}, schedule::{IntoSystemConfigs, SystemSet}};
 pub use cursor::*;
 pub use event::*;
+pub use frame_control::*;
 pub use system::*;
 pub use window::*;
 
@@ -78,6 +80,7 @@ pub enum FrameState {
     UnActive,
 }
 
+/// 帧是否需要运行：FrameState为Active（帧控制FrameControl不影响该条件，渲染和事件清理在暂停时仍照常运行）
 pub fn should_run(state: Res<FrameState>) -> bool {
     if let FrameState::Active = *state {
         true
@@ -86,6 +89,15 @@ pub fn should_run(state: Res<FrameState>) -> bool {
     }
 }
 
+/// 本帧是否需要推进模拟：FrameState为Active，并且帧控制（如果存在）本帧有需要推进的步数（暂停、未单步时为false）
+pub fn should_simulate(state: Res<FrameState>, control: Option<Res<FrameControl>>) -> bool {
+    if let FrameState::Active = *state {
+        control.map_or(true, |r| r.should_simulate())
+    } else {
+        false
+    }
+}
+
 #[derive(Debug, Clone, Hash, SystemSet, PartialEq, Eq)]
 pub struct FrameSet;
 
@@ -4,7 +4,7 @@ use std::{ops::Deref, sync::Arc};
 use bevy_ecs::prelude::{Res, Resource, ResMut, Commands, Entity};
 use bevy_ecs::system::CommandQueue;
 use bevy_app:: {Plugin, First};
-use pi_bevy_render_plugin::{node::Node, PiScreenTexture, PiRenderDevice, PiRenderWindow, PiRenderGraph, SimpleInOut, CLEAR_WIDNOW_NODE, render_cross::GraphId, NodeId};
+use pi_bevy_render_plugin::{node::Node, PiRenderOutput, PiRenderDevice, PiRenderGraph, SimpleInOut, CLEAR_WIDNOW_NODE, render_cross::GraphId, NodeId};
 use pi_render::{rhi::{pipeline::RenderPipeline, device::RenderDevice, BufferInitDescriptor, bind_group::BindGroup, sampler::SamplerDesc, bind_group_layout::BindGroupLayout, texture::PiRenderDefault, buffer::Buffer}, renderer::sampler::SamplerRes};
 use wgpu::Extent3d;
 use pi_null::Null;
@@ -252,7 +252,7 @@ impl Node for WindowRendererNode {
     type Output = ();
 
     type BuildParam = ();
-	type RunParam = (Res<'static, PiScreenTexture>, Res<'static, WindowRenderer>);
+	type RunParam = (Res<'static, PiRenderOutput>, Res<'static, WindowRenderer>);
 
 	fn build<'a>(
 		&'a mut self,
@@ -281,15 +281,16 @@ impl Node for WindowRendererNode {
 		_to: &'a [NodeId],
     ) -> pi_futures::BoxFuture<'a, Result<Self::Output, String>> {
 
-        let (screen, final_render) = param.get(world);
+        let (output, final_render) = param.get(world);
 
-        if final_render.pipeline.is_some() {
+        // 渲染到渲染输出上（窗口或离屏目标）
+        if let (Some(view), Some(_)) = (output.view.as_ref(), final_render.pipeline.as_ref()) {
             let mut rpass = commands.begin_render_pass(
                 &wgpu::RenderPassDescriptor {
                     label: Some(WindowRenderer::KEY),
                     color_attachments: &[
                         Some(wgpu::RenderPassColorAttachment {
-                            view: screen.0.as_ref().unwrap().view.as_ref().unwrap(),
+                            view,
                             resolve_target: None,
                             ops: wgpu::Operations {
                                 load: wgpu::LoadOp::Load,
@@ -397,12 +398,17 @@ impl Node for WindowRendererClearNode {
 }
 
 fn sys_changesize(
-    window: Res<PiRenderWindow>,
+    output: Res<PiRenderOutput>,
     device: Res<PiRenderDevice>,
     mut final_render: ResMut<WindowRenderer>,
 ) {
-    if window.width > 0 && window.height > 0 {
-        let surface_size = wgpu::Extent3d { width: window.width, height: window.height, depth_or_array_layers: 1 };
+    // 输出格式改变（如离屏目标的格式），需要重建管线
+    if final_render.surface_format != output.format {
+        final_render.surface_format = output.format;
+        final_render.pipeline = None;
+    }
+    if output.width > 0 && output.height > 0 {
+        let surface_size = wgpu::Extent3d { width: output.width, height: output.height, depth_or_array_layers: 1 };
         final_render.change(wgpu::TextureFormat::Rgba8Unorm, surface_size, &device);
     }
 }
@@ -0,0 +1,35 @@
+//! 离屏模式下运行渲染图（不需要窗口）
+//! 需要可用的GPU适配器（没有GPU的CI环境中无法创建设备），默认忽略，在有GPU的机器上运行：
+//! cargo test -p pi_window_renderer --test offscreen -- --ignored
+
+use bevy_app::App;
+use pi_bevy_asset::PiAssetPlugin;
+use pi_bevy_render_plugin::{OffscreenOptions, PiRenderOutput, PiRenderPlugin};
+use pi_window_renderer::{PluginWindowRender, WindowRenderer};
+
+#[test]
+#[ignore = "requires a GPU adapter"]
+fn offscreen_frames() {
+	let mut app = App::default();
+	app.add_plugins(PiAssetPlugin::default())
+		.add_plugins(PiRenderPlugin {
+			offscreen: Some(OffscreenOptions { width: 64, height: 32, ..Default::default() }),
+			..Default::default()
+		})
+		.add_plugins(PluginWindowRender);
+	app.finish();
+	app.cleanup();
+	app.world.resource_mut::<WindowRenderer>().active = true;
+
+	for _ in 0..3 {
+		app.update();
+	}
+
+	let output = app.world.resource::<PiRenderOutput>();
+	assert!(output.view.is_some());
+	assert_eq!((output.width, output.height), (64, 32));
+
+	let renderer = app.world.resource::<WindowRenderer>();
+	assert_eq!((renderer.size().width, renderer.size().height), (64, 32));
+	assert!(renderer.view().is_some());
+}